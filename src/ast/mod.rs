@@ -0,0 +1,512 @@
+use crate::json::Json;
+use crate::{Expr, MatchArm, Pattern, Statement, Token, TokenType};
+mod tests;
+
+/// Version of the serialized tree. Bump it whenever a node kind or field changes shape, so tools
+/// reading the output can tell which layout they're looking at.
+pub const AST_VERSION: u32 = 2;
+
+/// Serializes a whole program as `{"version", "kind": "Program", "body"}`.
+pub fn program_json(program: &[Expr]) -> Json {
+    Json::object(vec![
+        ("version", Json::from(AST_VERSION)),
+        ("kind", Json::from("Program")),
+        (
+            "body",
+            Json::Array(program.iter().map(Expr::to_json).collect()),
+        ),
+    ])
+}
+
+/// Serializes a whole program as an S-expression, one top-level statement per line.
+pub fn program_sexpr(program: &[Expr]) -> String {
+    let mut text = format!("(program (version {})", AST_VERSION);
+    for expr in program {
+        text.push_str("\n  ");
+        text.push_str(&expr.to_sexpr());
+    }
+    text.push(')');
+
+    text
+}
+
+impl Expr {
+    /// The node as JSON: a `kind`, its operators, literal values and children, and the `span`
+    /// of source it covers.
+    pub fn to_json(&self) -> Json {
+        expr_node(self).0
+    }
+
+    pub fn to_sexpr(&self) -> String {
+        match self {
+            Expr::Literal(token) => literal_sexpr(token),
+            Expr::Binary(left, operator, right) | Expr::Logical(left, operator, right) => {
+                format!(
+                    "({} {} {})",
+                    operator.lexeme,
+                    left.to_sexpr(),
+                    right.to_sexpr()
+                )
+            }
+            Expr::Unary(operator, operand) => {
+                format!("({} {})", operator.lexeme, operand.to_sexpr())
+            }
+            Expr::Ternary(condition, _, then, otherwise) => format!(
+                "(?: {} {} {})",
+                condition.to_sexpr(),
+                then.to_sexpr(),
+                otherwise.to_sexpr()
+            ),
+            Expr::Grouping(expr) => format!("(group {})", expr.to_sexpr()),
+            Expr::Stmt(statement) => statement.to_sexpr(),
+            Expr::Scope(_, exprs) => list("block", exprs.iter().map(Expr::to_sexpr)),
+            Expr::Map(_, entries) => list(
+                "map",
+                entries
+                    .iter()
+                    .map(|(key, value)| format!("({} {})", key.to_sexpr(), value.to_sexpr())),
+            ),
+            Expr::Index(target, _, key) => {
+                format!("(index {} {})", target.to_sexpr(), key.to_sexpr())
+            }
+            Expr::Call(callee, args) => list(
+                "call",
+                std::iter::once(callee.lexeme.clone()).chain(args.iter().map(Expr::to_sexpr)),
+            ),
+        }
+    }
+}
+
+impl Statement {
+    pub fn to_json(&self) -> Json {
+        statement_node(self).0
+    }
+
+    pub fn to_sexpr(&self) -> String {
+        match self {
+            Statement::PrintStmt(expr) => format!("(print {})", expr.to_sexpr()),
+            Statement::DeclarationStmt(variable, annotation, value) => {
+                let mut parts = vec![variable.lexeme.clone()];
+                if let Some(annotation) = annotation {
+                    parts.push(format!("(type {})", annotation.lexeme));
+                }
+                if let Some(value) = value {
+                    parts.push(value.to_sexpr());
+                }
+                list("var", parts.into_iter())
+            }
+            Statement::AssignmentStmt(variable, value) => {
+                format!("(assign {} {})", variable.lexeme, value.to_sexpr())
+            }
+            Statement::IndexAssignmentStmt(target, _, key, value) => format!(
+                "(assign (index {} {}) {})",
+                target.to_sexpr(),
+                key.to_sexpr(),
+                value.to_sexpr()
+            ),
+            Statement::CompoundAssignmentStmt(target, operator, value) => format!(
+                "({} {} {})",
+                operator.lexeme,
+                target.to_sexpr(),
+                value.to_sexpr()
+            ),
+            Statement::IncrementStmt(target, operator, postfix) => {
+                let position = if *postfix { "post" } else { "pre" };
+                format!("({}{} {})", position, operator.lexeme, target.to_sexpr())
+            }
+            Statement::IfStmt(conditionals) => list(
+                "if",
+                conditionals.iter().map(|conditional| match *conditional.0 {
+                    Expr::Grouping(_) => format!(
+                        "({} {})",
+                        conditional.0.to_sexpr(),
+                        conditional.1.to_sexpr()
+                    ),
+                    _ => format!("(else {})", conditional.1.to_sexpr()),
+                }),
+            ),
+            Statement::WhileStmt(conditional) => format!(
+                "(while {} {})",
+                conditional.0.to_sexpr(),
+                conditional.1.to_sexpr()
+            ),
+            Statement::ForStmt(init, condition, update, body) => {
+                let optional = |expr: &Option<Box<Expr>>| {
+                    expr.as_ref()
+                        .map_or(String::from("_"), |expr| expr.to_sexpr())
+                };
+                format!(
+                    "(for {} {} {} {})",
+                    optional(init),
+                    condition.to_sexpr(),
+                    optional(update),
+                    body.to_sexpr()
+                )
+            }
+            Statement::ImportStmt(path, names) => match names {
+                Some(names) => format!(
+                    "(import {} {})",
+                    Json::from(path.literal.as_str()),
+                    list("names", names.iter().map(|name| name.lexeme.clone()))
+                ),
+                None => format!("(import {})", Json::from(path.literal.as_str())),
+            },
+            Statement::ExportStmt(declaration) => format!("(export {})", declaration.to_sexpr()),
+            Statement::MatchStmt(_, value, arms) => list(
+                "match",
+                std::iter::once(value.to_sexpr()).chain(arms.iter().map(MatchArm::to_sexpr)),
+            ),
+        }
+    }
+}
+
+impl MatchArm {
+    fn to_sexpr(&self) -> String {
+        let patterns = self.patterns.iter().map(|pattern| match pattern {
+            Pattern::Value(value) => value.to_sexpr(),
+            Pattern::Wildcard(_) => String::from("_"),
+            Pattern::Binding(name) => format!("(bind {})", name.lexeme),
+        });
+        let mut parts = vec![list("patterns", patterns)];
+        if let Some(guard) = &self.guard {
+            parts.push(format!("(if {})", guard.to_sexpr()));
+        }
+        parts.push(self.body.to_sexpr());
+
+        list("arm", parts.into_iter())
+    }
+}
+
+fn list(head: &str, items: impl Iterator<Item = String>) -> String {
+    let mut text = format!("({}", head);
+    for item in items {
+        text.push(' ');
+        text.push_str(&item);
+    }
+    text.push(')');
+
+    text
+}
+
+fn literal_sexpr(token: &Token) -> String {
+    match token.token_type {
+        TokenType::NUMBER if integer_literal(token).is_some() => token.lexeme.clone(),
+        TokenType::NUMBER => token.literal.clone(),
+        TokenType::STRING => Json::from(token.literal.as_str()).to_string(),
+        _ => token.lexeme.clone(),
+    }
+}
+
+/// The value of a number literal written without a decimal point, which runs as an integer.
+/// Whole numbers too large for one stay floats, as they do when evaluated.
+fn integer_literal(token: &Token) -> Option<i64> {
+    match token.token_type {
+        TokenType::NUMBER => token.lexeme.parse().ok(),
+        _ => None,
+    }
+}
+
+/// A `[start, end)` range of source as 1-based (line, column) pairs.
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    start: (u32, u32),
+    end: (u32, u32),
+}
+
+impl Span {
+    /// Where a token sits in the source; `None` for tokens the parser made up, like the `true`
+    /// condition of an `else` branch.
+    fn of(token: &Token) -> Option<Span> {
+        if token.column == 0 {
+            return None;
+        }
+
+        let end = match token.lexeme.rsplit_once('\n') {
+            Some((before, last)) => (
+                token.line_num + before.matches('\n').count() as u32 + 1,
+                last.chars().count() as u32 + 1,
+            ),
+            None => (
+                token.line_num,
+                token.column + token.lexeme.chars().count() as u32,
+            ),
+        };
+        Some(Span {
+            start: (token.line_num, token.column),
+            end,
+        })
+    }
+
+    fn join(a: Option<Span>, b: Option<Span>) -> Option<Span> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(Span {
+                start: a.start.min(b.start),
+                end: a.end.max(b.end),
+            }),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn to_json(span: Option<Span>) -> Json {
+        let position = |(line, column): (u32, u32)| {
+            Json::object(vec![
+                ("line", Json::from(line)),
+                ("column", Json::from(column)),
+            ])
+        };
+
+        match span {
+            Some(span) => Json::object(vec![
+                ("start", position(span.start)),
+                ("end", position(span.end)),
+            ]),
+            None => Json::Null,
+        }
+    }
+}
+
+/// Builds one JSON node. Its span covers every token the node and its children keep; delimiters
+/// the parser drops, such as parentheses and semicolons, aren't part of the tree.
+struct Node {
+    fields: Vec<(&'static str, Json)>,
+    span: Option<Span>,
+}
+
+impl Node {
+    fn new(kind: &str) -> Self {
+        Node {
+            fields: vec![("kind", Json::from(kind))],
+            span: None,
+        }
+    }
+
+    fn token(mut self, token: &Token) -> Self {
+        self.span = Span::join(self.span, Span::of(token));
+        self
+    }
+
+    fn field(mut self, name: &'static str, value: Json) -> Self {
+        self.fields.push((name, value));
+        self
+    }
+
+    fn child(mut self, name: &'static str, expr: &Expr) -> Self {
+        let json = self.json(expr);
+        self.field(name, json)
+    }
+
+    fn optional(self, name: &'static str, expr: Option<&Expr>) -> Self {
+        match expr {
+            Some(expr) => self.child(name, expr),
+            None => self.field(name, Json::Null),
+        }
+    }
+
+    fn children(mut self, name: &'static str, exprs: &[Expr]) -> Self {
+        let items = exprs.iter().map(|expr| self.json(expr)).collect();
+        self.fields.push((name, Json::Array(items)));
+        self
+    }
+
+    /// Serializes a child, widening this node's span to cover it.
+    fn json(&mut self, expr: &Expr) -> Json {
+        let (json, span) = expr_node(expr);
+        self.span = Span::join(self.span, span);
+        json
+    }
+
+    fn finish(mut self) -> (Json, Option<Span>) {
+        self.fields.push(("span", Span::to_json(self.span)));
+        let entries = self
+            .fields
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        (Json::Object(entries), self.span)
+    }
+}
+
+fn expr_node(expr: &Expr) -> (Json, Option<Span>) {
+    let node = match expr {
+        Expr::Literal(token) if token.token_type == TokenType::IDENTIFIER => Node::new("Variable")
+            .field("name", Json::from(token.lexeme.as_str()))
+            .token(token),
+        Expr::Literal(token) => {
+            let (kind, value) = match (token.token_type, integer_literal(token)) {
+                (_, Some(val)) => ("integer", Json::from(val as f64)),
+                (TokenType::NUMBER, _) => ("number", Json::from(token.literal.parse::<f64>().ok())),
+                // as a string, since the digits may not fit in a JSON number
+                (TokenType::BIGINT, _) => ("bigint", Json::from(token.literal.as_str())),
+                (TokenType::STRING, _) => ("string", Json::from(token.literal.as_str())),
+                (TokenType::TRUE, _) => ("bool", Json::Bool(true)),
+                (TokenType::FALSE, _) => ("bool", Json::Bool(false)),
+                _ => ("nil", Json::Null),
+            };
+            Node::new("Literal")
+                .field("type", Json::from(kind))
+                .field("value", value)
+                .token(token)
+        }
+        Expr::Binary(left, operator, right) | Expr::Logical(left, operator, right) => {
+            let kind = match expr {
+                Expr::Logical(..) => "Logical",
+                _ => "Binary",
+            };
+            Node::new(kind)
+                .field("operator", Json::from(operator.lexeme.as_str()))
+                .token(operator)
+                .child("left", left)
+                .child("right", right)
+        }
+        Expr::Unary(operator, operand) => Node::new("Unary")
+            .field("operator", Json::from(operator.lexeme.as_str()))
+            .token(operator)
+            .child("operand", operand),
+        Expr::Ternary(condition, question, then, otherwise) => Node::new("Conditional")
+            .token(question)
+            .child("condition", condition)
+            .child("then", then)
+            .child("else", otherwise),
+        Expr::Grouping(expr) => Node::new("Grouping").child("expression", expr),
+        Expr::Stmt(statement) => return statement_node(statement),
+        Expr::Scope(brace, exprs) => Node::new("Block").token(brace).children("body", exprs),
+        Expr::Map(brace, entries) => {
+            let mut node = Node::new("Map").token(brace);
+            let entries = entries
+                .iter()
+                .map(|(key, value)| {
+                    Json::object(vec![("key", node.json(key)), ("value", node.json(value))])
+                })
+                .collect();
+            node.field("entries", Json::Array(entries))
+        }
+        Expr::Index(target, bracket, key) => Node::new("Index")
+            .token(bracket)
+            .child("target", target)
+            .child("index", key),
+        Expr::Call(callee, args) => Node::new("Call")
+            .field("callee", Json::from(callee.lexeme.as_str()))
+            .token(callee)
+            .children("arguments", args),
+    };
+
+    node.finish()
+}
+
+fn statement_node(statement: &Statement) -> (Json, Option<Span>) {
+    let node = match statement {
+        Statement::PrintStmt(expr) => Node::new("Print").child("expression", expr),
+        Statement::DeclarationStmt(variable, annotation, value) => {
+            let mut node = Node::new("Var")
+                .field("name", Json::from(variable.lexeme.as_str()))
+                .token(variable);
+            if let Some(annotation) = annotation {
+                node = node.token(annotation);
+            }
+            node.field(
+                "type",
+                Json::from(annotation.as_ref().map(|token| token.lexeme.as_str())),
+            )
+            .optional("initializer", value.as_deref())
+        }
+        Statement::AssignmentStmt(variable, value) => Node::new("Assign")
+            .field("name", Json::from(variable.lexeme.as_str()))
+            .token(variable)
+            .child("value", value),
+        Statement::IndexAssignmentStmt(target, bracket, key, value) => Node::new("IndexAssign")
+            .token(bracket)
+            .child("target", target)
+            .child("index", key)
+            .child("value", value),
+        Statement::CompoundAssignmentStmt(target, operator, value) => Node::new("CompoundAssign")
+            .field("operator", Json::from(operator.lexeme.as_str()))
+            .token(operator)
+            .child("target", target)
+            .child("value", value),
+        Statement::IncrementStmt(target, operator, postfix) => Node::new("Increment")
+            .field("operator", Json::from(operator.lexeme.as_str()))
+            .field("prefix", Json::from(!postfix))
+            .token(operator)
+            .child("target", target),
+        Statement::IfStmt(conditionals) => {
+            let mut node = Node::new("If");
+            let branches = conditionals
+                .iter()
+                .map(|conditional| {
+                    // the parser gives `else` a literal `true` rather than a condition
+                    let condition = match *conditional.0 {
+                        Expr::Grouping(_) => node.json(&conditional.0),
+                        _ => Json::Null,
+                    };
+                    Json::object(vec![
+                        ("condition", condition),
+                        ("body", node.json(&conditional.1)),
+                    ])
+                })
+                .collect();
+            node.field("branches", Json::Array(branches))
+        }
+        Statement::WhileStmt(conditional) => Node::new("While")
+            .child("condition", &conditional.0)
+            .child("body", &conditional.1),
+        Statement::ForStmt(init, condition, update, body) => Node::new("For")
+            .optional("initializer", init.as_deref())
+            .child("condition", condition)
+            .optional("increment", update.as_deref())
+            .child("body", body),
+        Statement::ImportStmt(path, names) => {
+            let mut node = Node::new("Import")
+                .field("path", Json::from(path.literal.as_str()))
+                .token(path);
+            let names = names.as_ref().map(|names| {
+                names
+                    .iter()
+                    .map(|name| {
+                        node.span = Span::join(node.span, Span::of(name));
+                        Json::from(name.lexeme.as_str())
+                    })
+                    .collect::<Vec<Json>>()
+            });
+            node.field("names", Json::from(names))
+        }
+        Statement::ExportStmt(declaration) => Node::new("Export").child("declaration", declaration),
+        Statement::MatchStmt(keyword, value, arms) => {
+            let mut node = Node::new("Match").token(keyword).child("value", value);
+            let mut items = vec![];
+            for arm in arms {
+                let mut patterns = vec![];
+                for pattern in arm.patterns.iter() {
+                    patterns.push(match pattern {
+                        Pattern::Value(value) => Json::object(vec![
+                            ("kind", Json::from("Value")),
+                            ("value", node.json(value)),
+                        ]),
+                        Pattern::Wildcard(token) => {
+                            node.span = Span::join(node.span, Span::of(token));
+                            Json::object(vec![("kind", Json::from("Wildcard"))])
+                        }
+                        Pattern::Binding(name) => {
+                            node.span = Span::join(node.span, Span::of(name));
+                            Json::object(vec![
+                                ("kind", Json::from("Binding")),
+                                ("name", Json::from(name.lexeme.as_str())),
+                            ])
+                        }
+                    });
+                }
+                let guard = match &arm.guard {
+                    Some(guard) => node.json(guard),
+                    None => Json::Null,
+                };
+                items.push(Json::object(vec![
+                    ("patterns", Json::Array(patterns)),
+                    ("guard", guard),
+                    ("body", node.json(&arm.body)),
+                ]));
+            }
+            node.field("arms", Json::Array(items))
+        }
+    };
+
+    node.finish()
+}
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use crate::ast::{program_json, program_sexpr};
+    use crate::interpreter::{parse, parse_expression};
+
+    #[test]
+    fn serializes_programs_as_json() {
+        let program = parse("var x: number = 1;\nprint x + 2;").unwrap();
+        assert_eq!(
+            program_json(&program).to_string(),
+            concat!(
+                r#"{"version":2,"kind":"Program","body":["#,
+                r#"{"kind":"Var","name":"x","type":"number","initializer":"#,
+                r#"{"kind":"Literal","type":"integer","value":1,"#,
+                r#""span":{"start":{"line":1,"column":17},"end":{"line":1,"column":18}}},"#,
+                r#""span":{"start":{"line":1,"column":5},"end":{"line":1,"column":18}}},"#,
+                r#"{"kind":"Print","expression":{"kind":"Binary","operator":"+","left":"#,
+                r#"{"kind":"Variable","name":"x","#,
+                r#""span":{"start":{"line":2,"column":7},"end":{"line":2,"column":8}}},"#,
+                r#""right":{"kind":"Literal","type":"integer","value":2,"#,
+                r#""span":{"start":{"line":2,"column":11},"end":{"line":2,"column":12}}},"#,
+                r#""span":{"start":{"line":2,"column":7},"end":{"line":2,"column":12}}},"#,
+                r#""span":{"start":{"line":2,"column":7},"end":{"line":2,"column":12}}}]}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn integer_literals_dump_apart_from_floats() {
+        let program = parse("print 1; print 1.0;").unwrap();
+        assert_eq!(
+            program_sexpr(&program),
+            "(program (version 2)\n  (print 1)\n  (print 1.0))"
+        );
+
+        let kind = |expr: &crate::Expr| {
+            let json = expr.to_json();
+            json.get("expression").unwrap().get("type").cloned()
+        };
+        assert_eq!(kind(&program[0]), Some("integer".into()));
+        assert_eq!(kind(&program[1]), Some("number".into()));
+    }
+
+    #[test]
+    fn else_branches_have_no_condition() {
+        let program = parse("if (a) print 1; else print 2;").unwrap();
+        let json = program[0].to_json();
+        let branches = json.get("branches").and_then(|b| b.as_array()).unwrap();
+        assert_eq!(
+            branches[0].get("condition").unwrap().get("kind"),
+            Some(&"Grouping".into())
+        );
+        assert_eq!(branches[1].get("condition"), Some(&crate::json::Json::Null));
+    }
+
+    #[test]
+    fn serializes_programs_as_sexprs() {
+        let source = "var m = {\"a\": 1};\nif (m[\"a\"] == 1 or false) { m[\"b\"] = -2; } else print clock();\nfor (var i = 0; i < 2; ) print i;\nimport { f } from \"lib.lox\";";
+        assert_eq!(
+            program_sexpr(&parse(source).unwrap()),
+            concat!(
+                "(program (version 2)\n",
+                "  (var m (map (\"a\" 1)))\n",
+                "  (if ((group (or (== (index m \"a\") 1) false)) (block (assign (index m \"b\") (- 2)))) (else (print (call clock))))\n",
+                "  (for (var i 0) (< i 2) _ (print i))\n",
+                "  (import \"lib.lox\" (names f)))",
+            )
+        );
+    }
+
+    #[test]
+    fn match_arms_list_their_patterns() {
+        let source = "match (x) { 1 | \"a\" => print 1, n if n => print n, _ => {} }";
+        assert_eq!(
+            program_sexpr(&parse(source).unwrap()),
+            concat!(
+                "(program (version 2)\n",
+                "  (match (group x) (arm (patterns 1 \"a\") (print 1)) ",
+                "(arm (patterns (bind n)) (if n) (print n)) (arm (patterns _) (block))))",
+            )
+        );
+    }
+
+    #[test]
+    fn logical_display_is_parenthesized() {
+        let expr = parse_expression("a and b or c").unwrap();
+        assert_eq!(expr.to_string(), "(or (and a b) c)");
+    }
+}
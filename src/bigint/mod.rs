@@ -0,0 +1,274 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+mod tests;
+
+/// Each limb holds nine decimal digits, so parsing and printing never need to divide.
+const BASE: u64 = 1_000_000_000;
+const BASE_DIGITS: usize = 9;
+
+/// An integer of any size, stored as its sign and magnitude.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BigInt {
+    negative: bool,
+    /// Least significant limb first, without leading zero limbs; empty for zero.
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    /// Reads an optional `-` followed by decimal digits.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, text),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let limbs = digits
+            .as_bytes()
+            .rchunks(BASE_DIGITS)
+            .map(|chunk| chunk.iter().fold(0, |acc, b| acc * 10 + (b - b'0') as u32))
+            .collect();
+
+        Some(BigInt::new(negative, limbs))
+    }
+
+    /// The exact value of a whole float, or `None` for fractions, infinities and NaN.
+    pub fn from_f64(val: f64) -> Option<Self> {
+        if !val.is_finite() || val.fract() != 0.0 {
+            return None;
+        }
+
+        BigInt::parse(&format!("{:.0}", val))
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        self.to_string().parse().ok()
+    }
+
+    /// The nearest float, or an infinity when the value is out of range.
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Division rounding the quotient down, with the remainder taking the sign of `divisor`.
+    /// Returns `None` when dividing by zero.
+    pub fn div_rem_floor(&self, divisor: &BigInt) -> Option<(BigInt, BigInt)> {
+        if divisor.is_zero() {
+            return None;
+        }
+
+        let (quotient, remainder) = div_rem_magnitudes(&self.limbs, &divisor.limbs);
+        let mut quotient = BigInt::new(self.negative != divisor.negative, quotient);
+        let mut remainder = BigInt::new(self.negative, remainder);
+        if !remainder.is_zero() && remainder.negative != divisor.negative {
+            quotient = quotient - BigInt::from(1);
+            remainder = remainder + divisor.clone();
+        }
+
+        Some((quotient, remainder))
+    }
+
+    fn new(negative: bool, mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+
+        BigInt {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+        }
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(val: i64) -> Self {
+        let mut magnitude = val.unsigned_abs();
+        let mut limbs = vec![];
+        while magnitude > 0 {
+            limbs.push((magnitude % BASE) as u32);
+            magnitude /= BASE;
+        }
+
+        BigInt::new(val < 0, limbs)
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some((last, rest)) = self.limbs.split_last() else {
+            return write!(f, "0");
+        };
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", last)?;
+        for limb in rest.iter().rev() {
+            write!(f, "{:09}", limb)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitudes(&self.limbs, &other.limbs),
+            (true, true) => cmp_magnitudes(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.limbs)
+    }
+}
+
+impl Add for BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_magnitudes(&self.limbs, &other.limbs));
+        }
+
+        // the sign of the result is the sign of the larger magnitude
+        match cmp_magnitudes(&self.limbs, &other.limbs) {
+            Ordering::Less => {
+                BigInt::new(other.negative, sub_magnitudes(&other.limbs, &self.limbs))
+            }
+            _ => BigInt::new(self.negative, sub_magnitudes(&self.limbs, &other.limbs)),
+        }
+    }
+}
+
+impl Sub for BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: BigInt) -> BigInt {
+        self + -other
+    }
+}
+
+impl Mul for BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: BigInt) -> BigInt {
+        let mut limbs = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let cur = limbs[i + j] + a as u64 * b as u64 + carry;
+                limbs[i + j] = cur % BASE;
+                carry = cur / BASE;
+            }
+            limbs[i + other.limbs.len()] += carry;
+        }
+
+        let limbs = limbs.into_iter().map(|limb| limb as u32).collect();
+        BigInt::new(self.negative != other.negative, limbs)
+    }
+}
+
+fn cmp_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut limbs = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+    for i in 0..a.len().max(b.len()) {
+        let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        limbs.push((sum % BASE) as u32);
+        carry = sum / BASE;
+    }
+    limbs.push(carry as u32);
+
+    limbs
+}
+
+/// `a - b`, where `a` is at least as large as `b`.
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut limbs = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, &limb) in a.iter().enumerate() {
+        let sub = *b.get(i).unwrap_or(&0) as i64 + borrow;
+        let mut diff = limb as i64 - sub;
+        borrow = 0;
+        if diff < 0 {
+            diff += BASE as i64;
+            borrow = 1;
+        }
+        limbs.push(diff as u32);
+    }
+
+    limbs
+}
+
+fn mul_small(a: &[u32], factor: u64) -> Vec<u32> {
+    let mut limbs = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0;
+    for &limb in a {
+        let cur = limb as u64 * factor + carry;
+        limbs.push((cur % BASE) as u32);
+        carry = cur / BASE;
+    }
+    limbs.push(carry as u32);
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+
+    limbs
+}
+
+/// Schoolbook long division, finding each quotient limb by binary search.
+fn div_rem_magnitudes(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    let mut quotient = vec![0; a.len()];
+    let mut remainder: Vec<u32> = vec![];
+    for i in (0..a.len()).rev() {
+        remainder.insert(0, a[i]);
+        while remainder.last() == Some(&0) {
+            remainder.pop();
+        }
+
+        let (mut low, mut high) = (0, BASE - 1);
+        while low < high {
+            let mid = (low + high).div_ceil(2);
+            match cmp_magnitudes(&mul_small(b, mid), &remainder) {
+                Ordering::Greater => high = mid - 1,
+                _ => low = mid,
+            }
+        }
+
+        if low > 0 {
+            remainder = sub_magnitudes(&remainder, &mul_small(b, low));
+            while remainder.last() == Some(&0) {
+                remainder.pop();
+            }
+        }
+        quotient[i] = low as u32;
+    }
+
+    (quotient, remainder)
+}
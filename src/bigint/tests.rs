@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests {
+    use crate::bigint::BigInt;
+
+    fn big(text: &str) -> BigInt {
+        BigInt::parse(text).unwrap()
+    }
+
+    #[test]
+    fn parses_and_prints_exactly() {
+        for text in ["0", "-1", "1000000000", "-123456789012345678901234567890"] {
+            assert_eq!(big(text).to_string(), text);
+        }
+        assert_eq!(big("-000").to_string(), "0");
+        assert_eq!(big("0012").to_string(), "12");
+        for text in ["", "-", "1.5", "12a", "+3"] {
+            assert!(BigInt::parse(text).is_none(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn arithmetic_matches_i128() {
+        let values: [i128; 8] = [
+            0,
+            1,
+            -7,
+            999_999_999,
+            1_000_000_000,
+            -123_456_789_012_345,
+            i64::MAX as i128,
+            i64::MIN as i128,
+        ];
+        for a in values {
+            for b in values {
+                let (x, y) = (big(&a.to_string()), big(&b.to_string()));
+                assert_eq!((x.clone() + y.clone()).to_string(), (a + b).to_string());
+                assert_eq!((x.clone() - y.clone()).to_string(), (a - b).to_string());
+                assert_eq!((x.clone() * y.clone()).to_string(), (a * b).to_string());
+                assert_eq!(x.cmp(&y), a.cmp(&b));
+                if b != 0 {
+                    let (quotient, remainder) = x.div_rem_floor(&y).unwrap();
+                    let floor = match a % b != 0 && (a < 0) != (b < 0) {
+                        true => a / b - 1,
+                        false => a / b,
+                    };
+                    assert_eq!(quotient.to_string(), floor.to_string());
+                    assert_eq!(
+                        (quotient * y + remainder.clone()).to_string(),
+                        a.to_string()
+                    );
+                    assert!(remainder.is_zero() || (remainder < BigInt::default()) == (b < 0));
+                }
+            }
+        }
+        assert!(big("5").div_rem_floor(&BigInt::default()).is_none());
+    }
+
+    #[test]
+    fn long_division_of_large_values() {
+        let factorial = (1..=30).fold(BigInt::from(1), |acc, n| acc * BigInt::from(n));
+        assert_eq!(factorial.to_string(), "265252859812191058636308480000000");
+
+        let (quotient, remainder) = factorial.div_rem_floor(&big("987654321987")).unwrap();
+        assert_eq!(quotient.to_string(), "268568520288095746722");
+        assert_eq!(remainder.to_string(), "253092223386");
+    }
+
+    #[test]
+    fn converts_to_and_from_other_numbers() {
+        assert_eq!(BigInt::from(i64::MIN).to_string(), "-9223372036854775808");
+        assert_eq!(big("9223372036854775807").to_i64(), Some(i64::MAX));
+        assert_eq!(big("9223372036854775808").to_i64(), None);
+        assert_eq!(BigInt::from_f64(1e20), Some(big("100000000000000000000")));
+        assert_eq!(BigInt::from_f64(1.5), None);
+        assert_eq!(big("-25").to_f64(), -25.0);
+    }
+}
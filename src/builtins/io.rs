@@ -0,0 +1,167 @@
+use super::{check_arity, get_string, maps, runtime_err, string};
+use crate::output::errln;
+use crate::Token;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Directories a script may touch through the file natives. Both are `None` unless the run was
+/// started with `--allow-read`/`--allow-write`.
+#[derive(Default)]
+pub struct Capabilities {
+    read_dir: Option<PathBuf>,
+    write_dir: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Access {
+    Read,
+    Write,
+}
+
+impl Capabilities {
+    pub fn new() -> Self {
+        Capabilities::default()
+    }
+
+    pub fn allow_read(&mut self, dir: &str) -> Result<(), ()> {
+        self.read_dir = Some(Self::canonical_dir(dir)?);
+        Ok(())
+    }
+
+    pub fn allow_write(&mut self, dir: &str) -> Result<(), ()> {
+        self.write_dir = Some(Self::canonical_dir(dir)?);
+        Ok(())
+    }
+
+    fn canonical_dir(dir: &str) -> Result<PathBuf, ()> {
+        match fs::canonicalize(dir) {
+            Ok(path) if path.is_dir() => Ok(path),
+            _ => {
+                errln!("Not a directory: {}", dir);
+                Err(())
+            }
+        }
+    }
+
+    pub fn call(&self, callee: &Token, args: &[Token]) -> Option<Result<Token, ()>> {
+        let res = match callee.lexeme.as_str() {
+            "readFile" => self.read_file(callee, args),
+            "readLines" => self.read_lines(callee, args),
+            "fileExists" => self.file_exists(callee, args),
+            "writeFile" => self.write_file(callee, args, false),
+            "appendFile" => self.write_file(callee, args, true),
+            _ => return None,
+        };
+
+        Some(res)
+    }
+
+    fn read_file(&self, callee: &Token, args: &[Token]) -> Result<Token, ()> {
+        check_arity(callee, args, 1)?;
+        let path = self.resolve(callee, &args[0], Access::Read)?;
+        match fs::read_to_string(&path) {
+            Ok(contents) => Ok(string(contents, callee.line_num)),
+            Err(err) => {
+                let message = format!("Could not read '{}': {}", args[0].literal, err);
+                runtime_err(callee, &message)?;
+                Err(())
+            }
+        }
+    }
+
+    fn read_lines(&self, callee: &Token, args: &[Token]) -> Result<Token, ()> {
+        let contents = self.read_file(callee, args)?;
+        let lines: Vec<Token> = contents
+            .literal
+            .lines()
+            .map(|line| string(line.to_string(), callee.line_num))
+            .collect();
+
+        Ok(maps::list(lines, callee.line_num))
+    }
+
+    fn file_exists(&self, callee: &Token, args: &[Token]) -> Result<Token, ()> {
+        check_arity(callee, args, 1)?;
+        let exists = match self.resolve(callee, &args[0], Access::Read) {
+            Ok(path) => path.is_file(),
+            Err(_) => return Err(()),
+        };
+
+        Ok(super::boolean(exists, callee.line_num))
+    }
+
+    fn write_file(&self, callee: &Token, args: &[Token], append: bool) -> Result<Token, ()> {
+        check_arity(callee, args, 2)?;
+        let path = self.resolve(callee, &args[0], Access::Write)?;
+        let contents = get_string(callee, &args[1])?;
+        let res = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(&path)
+            .and_then(|mut file| file.write_all(contents.as_bytes()));
+        if let Err(err) = res {
+            let message = format!("Could not write '{}': {}", args[0].literal, err);
+            runtime_err(callee, &message)?;
+        }
+
+        Ok(super::nil(callee.line_num))
+    }
+
+    /// Canonicalizes the script supplied path and checks that it stays inside the permitted
+    /// directory, so `..` and symlinks can't be used to escape it.
+    fn resolve(&self, callee: &Token, arg: &Token, access: Access) -> Result<PathBuf, ()> {
+        let (allowed, flag) = match access {
+            Access::Read => (&self.read_dir, "--allow-read"),
+            Access::Write => (&self.write_dir, "--allow-write"),
+        };
+        let Some(allowed) = allowed else {
+            let message = format!("'{}' requires the {} capability", callee.lexeme, flag);
+            runtime_err(callee, &message)?;
+            return Err(());
+        };
+
+        let raw_path = get_string(callee, arg)?;
+        let path = Path::new(&raw_path);
+        let canonical = match fs::canonicalize(path) {
+            Ok(canonical) => Some(canonical),
+            // files that don't exist yet are checked through their parent directory
+            Err(_) if fs::symlink_metadata(path).is_ok() => {
+                // a dangling symlink: writing through it could create a file anywhere
+                let message = format!("Path '{}' is a symlink to a missing file", raw_path);
+                runtime_err(callee, &message)?;
+                return Err(());
+            }
+            Err(_) => match (path.parent(), path.file_name()) {
+                (Some(parent), Some(name)) => {
+                    let parent = if parent.as_os_str().is_empty() {
+                        Path::new(".")
+                    } else {
+                        parent
+                    };
+                    fs::canonicalize(parent).ok().map(|dir| dir.join(name))
+                }
+                _ => None,
+            },
+        };
+
+        match canonical {
+            Some(canonical) if canonical.starts_with(allowed) => Ok(canonical),
+            Some(_) => {
+                let message = format!(
+                    "Path '{}' is outside the directory permitted by {}",
+                    raw_path, flag
+                );
+                runtime_err(callee, &message)?;
+                Err(())
+            }
+            None => {
+                let message = format!("Path '{}' does not exist", raw_path);
+                runtime_err(callee, &message)?;
+                Err(())
+            }
+        }
+    }
+}
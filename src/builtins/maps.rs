@@ -0,0 +1,39 @@
+use super::{check_arity, get_map, hash_check, integer, nil};
+use crate::{LoxMap, Token};
+
+pub fn has(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    check_arity(callee, &args, 2)?;
+    let map = get_map(callee, &args[0])?;
+    hash_check(callee, &args[1])?;
+    let found = map.borrow().contains(&args[1]);
+
+    Ok(super::boolean(found, callee.line_num))
+}
+
+pub fn delete(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    check_arity(callee, &args, 2)?;
+    let map = get_map(callee, &args[0])?;
+    hash_check(callee, &args[1])?;
+    let removed = map.borrow_mut().remove(&args[1]);
+
+    Ok(removed.unwrap_or_else(|| nil(callee.line_num)))
+}
+
+pub fn keys(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    // there are no lists, so keys come back as a map indexed 0..len in insertion order
+    check_arity(callee, &args, 1)?;
+    let map = get_map(callee, &args[0])?;
+    let keys = map.borrow().keys();
+
+    Ok(list(keys, callee.line_num))
+}
+
+/// Builds a map indexed `0..len`, which is how built-ins hand back sequences.
+pub fn list(items: Vec<Token>, line_num: u32) -> Token {
+    let mut map = LoxMap::new();
+    for (i, item) in items.into_iter().enumerate() {
+        map.insert(integer(i as i64, line_num), item);
+    }
+
+    Token::new_map(map, line_num)
+}
@@ -0,0 +1,140 @@
+use super::{check_arity, get_integer, get_number, integer, number, runtime_err};
+use crate::Token;
+use std::f32::consts;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub fn constant(name: &str, line_num: u32) -> Option<Token> {
+    match name {
+        "PI" => Some(number(consts::PI, line_num)),
+        "E" => Some(number(consts::E, line_num)),
+        _ => None,
+    }
+}
+
+pub fn call(callee: &Token, args: Vec<Token>) -> Option<Result<Token, ()>> {
+    let res = match callee.lexeme.as_str() {
+        "floor" => integral(callee, args, Some, f32::floor),
+        "ceil" => integral(callee, args, Some, f32::ceil),
+        "round" => integral(callee, args, Some, f32::round),
+        "abs" => integral(callee, args, i64::checked_abs, f32::abs),
+        "sqrt" => unary(callee, args, f32::sqrt),
+        "sin" => unary(callee, args, f32::sin),
+        "cos" => unary(callee, args, f32::cos),
+        "tan" => unary(callee, args, f32::tan),
+        "asin" => unary(callee, args, f32::asin),
+        "acos" => unary(callee, args, f32::acos),
+        "atan" => unary(callee, args, f32::atan),
+        "exp" => unary(callee, args, f32::exp),
+        "log" => unary(callee, args, f32::ln),
+        "log2" => unary(callee, args, f32::log2),
+        "log10" => unary(callee, args, f32::log10),
+        "pow" => binary(callee, args, f32::powf),
+        "atan2" => binary(callee, args, f32::atan2),
+        "min" => fold(callee, args, i64::min, f32::min),
+        "max" => fold(callee, args, i64::max, f32::max),
+        _ => return None,
+    };
+
+    Some(res)
+}
+
+fn unary(callee: &Token, args: Vec<Token>, op: fn(f32) -> f32) -> Result<Token, ()> {
+    check_arity(callee, &args, 1)?;
+    let val = get_number(callee, &args[0])?;
+
+    Ok(number(op(val), callee.line_num))
+}
+
+/// Like `unary`, but an integer argument gives an exact integer through `int_op`, which returns
+/// `None` on overflow.
+fn integral(
+    callee: &Token,
+    args: Vec<Token>,
+    int_op: fn(i64) -> Option<i64>,
+    op: fn(f32) -> f32,
+) -> Result<Token, ()> {
+    check_arity(callee, &args, 1)?;
+    let Some(val) = get_integer(&args[0]) else {
+        return unary(callee, args, op);
+    };
+
+    match int_op(val) {
+        Some(res) => Ok(integer(res, callee.line_num)),
+        None => {
+            runtime_err(callee, "Integer overflow")?;
+            Err(())
+        }
+    }
+}
+
+fn binary(callee: &Token, args: Vec<Token>, op: fn(f32, f32) -> f32) -> Result<Token, ()> {
+    check_arity(callee, &args, 2)?;
+    let val1 = get_number(callee, &args[0])?;
+    let val2 = get_number(callee, &args[1])?;
+
+    Ok(number(op(val1, val2), callee.line_num))
+}
+
+/// Combines the arguments with `int_op` if they're all integers, otherwise with `op` on floats.
+fn fold(
+    callee: &Token,
+    args: Vec<Token>,
+    int_op: fn(i64, i64) -> i64,
+    op: fn(f32, f32) -> f32,
+) -> Result<Token, ()> {
+    if args.is_empty() {
+        let message = format!("'{}' expects at least one argument", callee.lexeme);
+        runtime_err(callee, &message)?;
+    }
+
+    let vals: Option<Vec<i64>> = args.iter().map(get_integer).collect();
+    if let Some(res) = vals.and_then(|vals| vals.into_iter().reduce(int_op)) {
+        return Ok(integer(res, callee.line_num));
+    }
+
+    let mut res = get_number(callee, &args[0])?;
+    for arg in &args[1..] {
+        res = op(res, get_number(callee, arg)?);
+    }
+
+    Ok(number(res, callee.line_num))
+}
+
+/// Deterministic generator behind `random()` (splitmix64), so runs started with the same
+/// `--seed` produce the same numbers.
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Random { state: seed }
+    }
+
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or(0);
+
+        Random::new(nanos)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `[0, 1)`.
+    pub fn next_number(&mut self, callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+        check_arity(callee, &args, 0)?;
+        // 24 random bits fill an f32 mantissa exactly, so the result never rounds up to 1
+        let val = (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32;
+
+        Ok(number(val, callee.line_num))
+    }
+}
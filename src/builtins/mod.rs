@@ -0,0 +1,188 @@
+use crate::diagnostics::{report, Diagnostic};
+use crate::{BigInt, LoxMap, Token, TokenType};
+use std::cell::RefCell;
+use std::rc::Rc;
+mod io;
+mod maps;
+mod math;
+mod numbers;
+mod strings;
+mod tests;
+
+pub use io::Capabilities;
+pub use math::{constant, Random};
+pub use strings::index_string;
+
+/// Calls the built-in function named by `callee`. Returns `None` if there is no such built-in.
+pub fn call(callee: &Token, args: Vec<Token>) -> Option<Result<Token, ()>> {
+    let res = match callee.lexeme.as_str() {
+        "len" => len(callee, args),
+        "has" => maps::has(callee, args),
+        "delete" => maps::delete(callee, args),
+        "keys" => maps::keys(callee, args),
+        "substr" => strings::substr(callee, args),
+        "indexOf" => strings::index_of(callee, args),
+        "split" => strings::split(callee, args),
+        "join" => strings::join(callee, args),
+        "upper" => strings::upper(callee, args),
+        "lower" => strings::lower(callee, args),
+        "trim" => strings::trim(callee, args),
+        "replace" => strings::replace(callee, args),
+        "startsWith" => strings::starts_with(callee, args),
+        "endsWith" => strings::ends_with(callee, args),
+        "charAt" => strings::char_at(callee, args),
+        "bigint" => numbers::to_bigint(callee, args),
+        "number" => numbers::to_number(callee, args),
+        _ => return math::call(callee, args),
+    };
+
+    Some(res)
+}
+
+fn len(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    check_arity(callee, &args, 1)?;
+    let len = match args[0].token_type {
+        TokenType::STRING => args[0].literal.chars().count(),
+        TokenType::MAP => get_map(callee, &args[0])?.borrow().len(),
+        _ => {
+            runtime_err(callee, "Argument to 'len' must be a string or a map")?;
+            0
+        }
+    };
+
+    Ok(integer(len as i64, callee.line_num))
+}
+
+pub fn check_arity(callee: &Token, args: &[Token], arity: usize) -> Result<(), ()> {
+    if args.len() != arity {
+        let message = format!("Expected {} arguments but got {}.", arity, args.len());
+        report(&Diagnostic::runtime(callee, &message));
+        return Err(());
+    }
+
+    Ok(())
+}
+
+pub fn hash_check(token: &Token, key: &Token) -> Result<(), ()> {
+    if LoxMap::hash_key(key).is_none() {
+        runtime_err(token, "Map keys must be strings, numbers, booleans or nil")?;
+    }
+
+    Ok(())
+}
+
+fn get_map(callee: &Token, arg: &Token) -> Result<Rc<RefCell<LoxMap>>, ()> {
+    match arg.as_map() {
+        Some(map) => Ok(map),
+        None => {
+            let message = format!("Argument to '{}' must be a map", callee.lexeme);
+            runtime_err(callee, &message)?;
+            Err(())
+        }
+    }
+}
+
+fn get_string(callee: &Token, arg: &Token) -> Result<String, ()> {
+    if arg.token_type != TokenType::STRING {
+        let message = format!("Argument to '{}' must be a string", callee.lexeme);
+        runtime_err(callee, &message)?;
+    }
+
+    Ok(arg.literal.clone())
+}
+
+fn get_number(callee: &Token, arg: &Token) -> Result<f32, ()> {
+    if !arg.token_type.is_number() {
+        let message = format!("Argument to '{}' must be a number", callee.lexeme);
+        runtime_err(callee, &message)?;
+    }
+
+    Ok(arg.literal.parse::<f32>().unwrap())
+}
+
+/// The value of an integer argument, or `None` for a float or anything that isn't a number.
+fn get_integer(arg: &Token) -> Option<i64> {
+    match arg.token_type {
+        TokenType::INTEGER => arg.literal.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Reads a non-negative whole number, e.g. a character position.
+fn get_index(callee: &Token, arg: &Token) -> Result<usize, ()> {
+    if arg.token_type.is_number() {
+        let val = arg.literal.parse::<f32>().unwrap();
+        if val >= 0.0 && val.fract() == 0.0 {
+            return Ok(val as usize);
+        }
+    }
+
+    let message = format!("Index passed to '{}' must be a whole number", callee.lexeme);
+    runtime_err(callee, &message)?;
+    Err(())
+}
+
+#[inline]
+pub fn runtime_err(token: &Token, message: &str) -> Result<(), ()> {
+    report(&Diagnostic::runtime(token, &format!("{}.", message)));
+
+    Err(())
+}
+
+pub fn number(val: f32, line_num: u32) -> Token {
+    let mut literal = val.to_string();
+    if literal.parse::<i32>().is_ok() {
+        literal = format!("{}.0", literal);
+    }
+
+    Token::new(TokenType::NUMBER, val.to_string(), literal, line_num)
+}
+
+pub fn bigint(val: &BigInt, line_num: u32) -> Token {
+    Token::new(
+        TokenType::BIGINT,
+        val.to_string(),
+        val.to_string(),
+        line_num,
+    )
+}
+
+pub fn integer(val: i64, line_num: u32) -> Token {
+    Token::new(
+        TokenType::INTEGER,
+        val.to_string(),
+        val.to_string(),
+        line_num,
+    )
+}
+
+pub fn string(val: String, line_num: u32) -> Token {
+    Token::new(TokenType::STRING, format!("\"{}\"", val), val, line_num)
+}
+
+pub fn boolean(flag: bool, line_num: u32) -> Token {
+    if flag {
+        return Token::new(
+            TokenType::TRUE,
+            String::from("true"),
+            String::from("null"),
+            line_num,
+        );
+    }
+
+    Token::new(
+        TokenType::FALSE,
+        String::from("false"),
+        String::from("null"),
+        line_num,
+    )
+}
+
+pub fn nil(line_num: u32) -> Token {
+    Token::new(
+        TokenType::NIL,
+        String::from("nil"),
+        String::from("null"),
+        line_num,
+    )
+}
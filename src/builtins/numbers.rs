@@ -0,0 +1,47 @@
+use super::{bigint, check_arity, integer, number, runtime_err};
+use crate::{BigInt, Token, TokenType};
+
+/// `bigint(x)` makes a big integer from an integer, a whole float or a string of digits.
+pub fn to_bigint(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    check_arity(callee, &args, 1)?;
+    let arg = &args[0];
+    let val = match arg.token_type {
+        TokenType::BIGINT | TokenType::INTEGER => BigInt::parse(&arg.literal),
+        TokenType::NUMBER => arg.literal.parse().ok().and_then(BigInt::from_f64),
+        TokenType::STRING => BigInt::parse(arg.literal.trim()),
+        _ => None,
+    };
+
+    match val {
+        Some(val) => Ok(bigint(&val, callee.line_num)),
+        None => {
+            let message = "Argument to 'bigint' must be a whole number or a string of digits";
+            runtime_err(callee, message)?;
+            Err(())
+        }
+    }
+}
+
+/// `number(x)` turns a big integer back into an integer, or into the nearest float when it
+/// doesn't fit in 64 bits. Other numbers are returned unchanged.
+pub fn to_number(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    check_arity(callee, &args, 1)?;
+    let arg = args.into_iter().next().unwrap();
+    match arg.token_type {
+        TokenType::NUMBER | TokenType::INTEGER => Ok(arg),
+        TokenType::BIGINT => {
+            let val = BigInt::parse(&arg.literal).unwrap_or_default();
+            match val.to_i64() {
+                Some(val) => Ok(integer(val, callee.line_num)),
+                None => Ok(number(val.to_f64() as f32, callee.line_num)),
+            }
+        }
+        _ => {
+            runtime_err(
+                callee,
+                "Argument to 'number' must be a number or a big integer",
+            )?;
+            Err(())
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use super::{boolean, check_arity, get_index, get_map, get_string, integer, runtime_err, string};
+use crate::builtins::maps;
+use crate::Token;
+
+// positions and lengths are counted in unicode scalar values, not bytes
+
+pub fn substr(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    check_arity(callee, &args, 3)?;
+    let val = get_string(callee, &args[0])?;
+    let start = get_index(callee, &args[1])?;
+    let len = get_index(callee, &args[2])?;
+    let char_count = val.chars().count();
+    if start.checked_add(len).map_or(true, |end| end > char_count) {
+        // indices saturate at `usize::MAX`, so the end is only printed in a wider type
+        let message = format!(
+            "Substring {}..{} is out of range for a string of length {}",
+            start,
+            start as u128 + len as u128,
+            char_count
+        );
+        runtime_err(callee, &message)?;
+    }
+
+    let res: String = val.chars().skip(start).take(len).collect();
+    Ok(string(res, callee.line_num))
+}
+
+pub fn index_of(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    check_arity(callee, &args, 2)?;
+    let val = get_string(callee, &args[0])?;
+    let needle = get_string(callee, &args[1])?;
+    let pos = match val.find(&needle) {
+        Some(byte_pos) => val[..byte_pos].chars().count() as i64,
+        None => -1,
+    };
+
+    Ok(integer(pos, callee.line_num))
+}
+
+pub fn split(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    check_arity(callee, &args, 2)?;
+    let val = get_string(callee, &args[0])?;
+    let separator = get_string(callee, &args[1])?;
+    let parts: Vec<Token> = if separator.is_empty() {
+        val.chars()
+            .map(|ch| string(ch.to_string(), callee.line_num))
+            .collect()
+    } else {
+        val.split(separator.as_str())
+            .map(|part| string(part.to_string(), callee.line_num))
+            .collect()
+    };
+
+    Ok(maps::list(parts, callee.line_num))
+}
+
+pub fn join(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    check_arity(callee, &args, 2)?;
+    let map = get_map(callee, &args[0])?;
+    let separator = get_string(callee, &args[1])?;
+    let mut parts: Vec<String> = vec![];
+    for i in 0..map.borrow().len() {
+        match map.borrow().get(&integer(i as i64, callee.line_num)) {
+            Some(part) => parts.push(part.value_string()),
+            None => {
+                runtime_err(callee, "Argument to 'join' must be indexed from 0")?;
+            }
+        }
+    }
+
+    Ok(string(parts.join(&separator), callee.line_num))
+}
+
+pub fn upper(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    check_arity(callee, &args, 1)?;
+    let val = get_string(callee, &args[0])?;
+
+    Ok(string(val.to_uppercase(), callee.line_num))
+}
+
+pub fn lower(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    check_arity(callee, &args, 1)?;
+    let val = get_string(callee, &args[0])?;
+
+    Ok(string(val.to_lowercase(), callee.line_num))
+}
+
+pub fn trim(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    check_arity(callee, &args, 1)?;
+    let val = get_string(callee, &args[0])?;
+
+    Ok(string(val.trim().to_string(), callee.line_num))
+}
+
+pub fn replace(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    check_arity(callee, &args, 3)?;
+    let val = get_string(callee, &args[0])?;
+    let from = get_string(callee, &args[1])?;
+    let to = get_string(callee, &args[2])?;
+    if from.is_empty() {
+        runtime_err(callee, "Cannot replace an empty string")?;
+    }
+
+    Ok(string(val.replace(&from, &to), callee.line_num))
+}
+
+pub fn starts_with(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    check_arity(callee, &args, 2)?;
+    let val = get_string(callee, &args[0])?;
+    let prefix = get_string(callee, &args[1])?;
+
+    Ok(boolean(val.starts_with(&prefix), callee.line_num))
+}
+
+pub fn ends_with(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    check_arity(callee, &args, 2)?;
+    let val = get_string(callee, &args[0])?;
+    let suffix = get_string(callee, &args[1])?;
+
+    Ok(boolean(val.ends_with(&suffix), callee.line_num))
+}
+
+pub fn char_at(callee: &Token, args: Vec<Token>) -> Result<Token, ()> {
+    check_arity(callee, &args, 2)?;
+    let val = get_string(callee, &args[0])?;
+    let index = get_index(callee, &args[1])?;
+
+    get_char(callee, &val, index)
+}
+
+/// Shared by `charAt(s, i)` and `s[i]`.
+pub fn get_char(token: &Token, val: &str, index: usize) -> Result<Token, ()> {
+    match val.chars().nth(index) {
+        Some(ch) => Ok(string(ch.to_string(), token.line_num)),
+        None => {
+            let message = format!(
+                "Index {} is out of range for a string of length {}",
+                index,
+                val.chars().count()
+            );
+            runtime_err(token, &message)?;
+            Err(())
+        }
+    }
+}
+
+/// Evaluates `s[i]` for a string `s`.
+pub fn index_string(bracket: &Token, target: &Token, key: &Token) -> Result<Token, ()> {
+    let index = get_index(bracket, key)?;
+
+    get_char(bracket, &target.literal, index)
+}
@@ -0,0 +1,247 @@
+#[cfg(test)]
+mod tests {
+    use crate::builtins::{self, boolean, integer, nil, number, Capabilities, Random};
+    use crate::{LoxMap, Token, TokenType};
+    use std::fs;
+
+    fn callee(name: &str) -> Token {
+        Token::new(
+            TokenType::IDENTIFIER,
+            String::from(name),
+            String::from("null"),
+            1,
+        )
+    }
+
+    fn string(val: &str) -> Token {
+        Token::new(
+            TokenType::STRING,
+            format!("\"{}\"", val),
+            String::from(val),
+            1,
+        )
+    }
+
+    fn sample_map() -> Token {
+        let mut map = LoxMap::new();
+        map.insert(string("b"), number(2.0, 1));
+        map.insert(string("a"), number(1.0, 1));
+        map.insert(nil(1), boolean(true, 1));
+
+        Token::new_map(map, 1)
+    }
+
+    #[test]
+    fn map_keys_keep_insertion_order() {
+        let map = sample_map();
+        let res = builtins::call(&callee("keys"), vec![map]).unwrap().unwrap();
+        assert_eq!(res.value_string(), "{0: \"b\", 1: \"a\", 2: nil}");
+    }
+
+    #[test]
+    fn maps_that_contain_themselves_still_print() {
+        let map = sample_map();
+        let inner = Token::new_map(LoxMap::new(), 1);
+        inner
+            .as_map()
+            .unwrap()
+            .borrow_mut()
+            .insert(string("m"), map.clone());
+        map.as_map()
+            .unwrap()
+            .borrow_mut()
+            .insert(string("self"), map.clone());
+        map.as_map()
+            .unwrap()
+            .borrow_mut()
+            .insert(string("inner"), inner);
+        assert_eq!(
+            map.value_string(),
+            "{\"b\": 2, \"a\": 1, nil: true, \"self\": {...}, \"inner\": {\"m\": {...}}}"
+        );
+    }
+
+    #[test]
+    fn map_has_and_delete() {
+        let map = sample_map();
+        let has = builtins::call(&callee("has"), vec![map.clone(), string("a")]);
+        assert_eq!(has.unwrap().unwrap().token_type, TokenType::TRUE);
+
+        let removed = builtins::call(&callee("delete"), vec![map.clone(), string("a")]);
+        assert_eq!(removed.unwrap().unwrap().lexeme, "1");
+        assert_eq!(map.value_string(), "{\"b\": 2, nil: true}");
+
+        let has = builtins::call(&callee("has"), vec![map, string("a")]);
+        assert_eq!(has.unwrap().unwrap().token_type, TokenType::FALSE);
+    }
+
+    #[test]
+    fn map_rejects_unhashable_keys() {
+        let map = sample_map();
+        let res = builtins::call(&callee("has"), vec![map.clone(), sample_map()]);
+        assert!(res.unwrap().is_err());
+
+        let mut inner = LoxMap::new();
+        assert!(!inner.insert(map, nil(1)));
+    }
+
+    #[test]
+    fn converts_between_numbers_and_big_integers() {
+        let res = builtins::call(&callee("bigint"), vec![string(" -12345678901234567890 ")]);
+        let big = res.unwrap().unwrap();
+        assert_eq!(big.token_type, TokenType::BIGINT);
+        assert_eq!(big.lexeme, "-12345678901234567890");
+
+        let res = builtins::call(&callee("number"), vec![big])
+            .unwrap()
+            .unwrap();
+        assert_eq!(res.token_type, TokenType::NUMBER);
+        let res = builtins::call(&callee("bigint"), vec![number(4.0, 1)]);
+        let res = builtins::call(&callee("number"), vec![res.unwrap().unwrap()]);
+        assert_eq!(res.unwrap().unwrap().token_type, TokenType::INTEGER);
+
+        for arg in [number(1.5, 1), string("12a"), nil(1)] {
+            assert!(builtins::call(&callee("bigint"), vec![arg])
+                .unwrap()
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn unknown_builtin() {
+        assert!(builtins::call(&callee("nope"), vec![]).is_none());
+    }
+
+    #[test]
+    fn string_functions_count_unicode_scalars() {
+        let res = builtins::call(&callee("len"), vec![string("héllo")]);
+        assert_eq!(res.unwrap().unwrap().lexeme, "5");
+
+        let args = vec![string("héllo"), number(1.0, 1), number(3.0, 1)];
+        let res = builtins::call(&callee("substr"), args);
+        assert_eq!(res.unwrap().unwrap().literal, "éll");
+
+        let args = vec![string("abc"), number(1.0, 1), number(1e23, 1)];
+        assert!(builtins::call(&callee("substr"), args).unwrap().is_err());
+
+        let res = builtins::call(&callee("indexOf"), vec![string("héllo"), string("l")]);
+        assert_eq!(res.unwrap().unwrap().lexeme, "2");
+    }
+
+    #[test]
+    fn split_and_join_round_trip() {
+        let parts = builtins::call(&callee("split"), vec![string("a,b,,c"), string(",")]);
+        let parts = parts.unwrap().unwrap();
+        assert_eq!(
+            parts.value_string(),
+            "{0: \"a\", 1: \"b\", 2: \"\", 3: \"c\"}"
+        );
+
+        let res = builtins::call(&callee("join"), vec![parts, string(",")]);
+        assert_eq!(res.unwrap().unwrap().literal, "a,b,,c");
+    }
+
+    #[test]
+    fn bad_string_index_is_an_error() {
+        let res = builtins::call(&callee("charAt"), vec![string("ab"), number(2.0, 1)]);
+        assert!(res.unwrap().is_err());
+
+        let res = builtins::call(&callee("charAt"), vec![string("ab"), number(0.5, 1)]);
+        assert!(res.unwrap().is_err());
+    }
+
+    #[test]
+    fn math_functions() {
+        let res = builtins::call(&callee("floor"), vec![number(-2.5, 1)]);
+        assert_eq!(res.unwrap().unwrap().literal, "-3.0");
+
+        let res = builtins::call(&callee("pow"), vec![number(2.0, 1), number(8.0, 1)]);
+        assert_eq!(res.unwrap().unwrap().lexeme, "256");
+
+        let args = vec![number(4.0, 1), number(-1.0, 1), number(9.0, 1)];
+        let res = builtins::call(&callee("min"), args);
+        assert_eq!(res.unwrap().unwrap().lexeme, "-1");
+
+        let res = builtins::call(&callee("sqrt"), vec![string("4")]);
+        assert!(res.unwrap().is_err());
+    }
+
+    #[test]
+    fn math_functions_keep_integers_exact() {
+        let large = || integer(16_777_217, 1);
+        for (name, args, expected) in [
+            ("abs", vec![integer(-16_777_217, 1)], "16777217"),
+            ("floor", vec![large()], "16777217"),
+            ("ceil", vec![large()], "16777217"),
+            ("round", vec![large()], "16777217"),
+            ("max", vec![large(), integer(1, 1)], "16777217"),
+            ("min", vec![large(), large()], "16777217"),
+        ] {
+            let res = builtins::call(&callee(name), args).unwrap().unwrap();
+            assert_eq!(res.token_type, TokenType::INTEGER, "{}", name);
+            assert_eq!(res.lexeme, expected, "{}", name);
+        }
+
+        let res = builtins::call(&callee("max"), vec![integer(1, 1), number(1.5, 1)]);
+        assert_eq!(res.unwrap().unwrap().token_type, TokenType::NUMBER);
+
+        let res = builtins::call(&callee("abs"), vec![integer(i64::MIN, 1)]);
+        assert!(res.unwrap().is_err());
+    }
+
+    #[test]
+    fn seeded_random_is_reproducible() {
+        let mut first = Random::new(7);
+        let mut second = Random::new(7);
+        for _ in 0..5 {
+            let val1 = first.next_number(&callee("random"), vec![]).unwrap();
+            let val2 = second.next_number(&callee("random"), vec![]).unwrap();
+            assert_eq!(val1.literal, val2.literal);
+
+            let val = val1.literal.parse::<f32>().unwrap();
+            assert!((0.0..1.0).contains(&val));
+        }
+    }
+
+    #[test]
+    fn file_natives_need_a_capability() {
+        let capabilities = Capabilities::new();
+        let res = capabilities.call(&callee("readFile"), &[string("Cargo.toml")]);
+        assert!(res.unwrap().is_err());
+        assert!(capabilities.call(&callee("len"), &[]).is_none());
+    }
+
+    #[test]
+    fn file_natives_stay_inside_the_permitted_directory() {
+        let root = std::env::temp_dir().join(format!("lox-io-{}", std::process::id()));
+        let allowed = root.join("allowed");
+        fs::create_dir_all(&allowed).unwrap();
+        fs::write(root.join("outside.txt"), "nope").unwrap();
+
+        let mut capabilities = Capabilities::new();
+        capabilities.allow_read(allowed.to_str().unwrap()).unwrap();
+        capabilities.allow_write(allowed.to_str().unwrap()).unwrap();
+
+        let inside = allowed.join("notes.txt").to_str().unwrap().to_string();
+        let res = capabilities.call(&callee("writeFile"), &[string(&inside), string("a\nb")]);
+        assert!(res.unwrap().is_ok());
+        let res = capabilities.call(&callee("readLines"), &[string(&inside)]);
+        assert_eq!(res.unwrap().unwrap().value_string(), "{0: \"a\", 1: \"b\"}");
+
+        let escape = allowed.join("../outside.txt").to_str().unwrap().to_string();
+        let res = capabilities.call(&callee("readFile"), &[string(&escape)]);
+        assert!(res.unwrap().is_err());
+
+        #[cfg(unix)]
+        {
+            let link = allowed.join("link.txt");
+            std::os::unix::fs::symlink(root.join("created.txt"), &link).unwrap();
+            let link = link.to_str().unwrap().to_string();
+            let res = capabilities.call(&callee("writeFile"), &[string(&link), string("x")]);
+            assert!(res.unwrap().is_err());
+            assert!(!root.join("created.txt").exists());
+        }
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
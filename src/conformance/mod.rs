@@ -0,0 +1,300 @@
+use crate::diagnostics::{error_format, set_error_format, ErrorFormat};
+use crate::{Interpreter, SharedBuffer};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+mod tests;
+
+/// What a script's annotations say running it should produce.
+#[derive(Debug, Default, PartialEq)]
+struct Expectations {
+    /// `// expect: value`, one per printed line, in order.
+    output: Vec<String>,
+    /// `// Error at 'x': message` or `// [line N] Error...`, as the short format writes them.
+    compile_errors: Vec<String>,
+    /// `// Warning at 'x': message`. Warnings without an annotation are ignored.
+    warnings: Vec<String>,
+    /// `// expect runtime error: message` and the line it's on.
+    runtime_error: Option<(String, u32)>,
+    /// `// nontest` marks helper files that aren't tests themselves.
+    skip: bool,
+}
+
+impl Expectations {
+    fn parse(source: &str) -> Self {
+        let mut expectations = Expectations::default();
+        for (line_num, line) in (1..).zip(source.lines()) {
+            // a `// ` inside a string literal isn't an annotation, so try each one in turn
+            for (pos, _) in line.match_indices("// ") {
+                if expectations.annotate(&line[pos + 3..], line_num) {
+                    break;
+                }
+            }
+        }
+
+        expectations
+    }
+
+    /// Records the annotation `comment` starts with, if any.
+    fn annotate(&mut self, comment: &str, line_num: u32) -> bool {
+        if let Some(value) = comment.strip_prefix("expect: ") {
+            self.output.push(value.to_string());
+        } else if let Some(message) = comment.strip_prefix("expect runtime error: ") {
+            self.runtime_error = Some((message.to_string(), line_num));
+        } else if comment.starts_with("Error") {
+            let error = format!("[line {}] {}", line_num, comment);
+            self.compile_errors.push(error);
+        } else if comment.starts_with("Warning") {
+            let warning = format!("[line {}] {}", line_num, comment);
+            self.warnings.push(warning);
+        } else if let Some(error) = explicit_line_error(comment) {
+            self.compile_errors.push(error);
+        } else if comment.starts_with("nontest") {
+            self.skip = true;
+        } else {
+            return false;
+        }
+
+        true
+    }
+
+    fn count(&self) -> usize {
+        self.output.len()
+            + self.compile_errors.len()
+            + self.warnings.len()
+            + self.runtime_error.iter().count()
+    }
+
+    fn exit_code(&self) -> i32 {
+        match (self.compile_errors.is_empty(), &self.runtime_error) {
+            (false, _) => 65,
+            (true, Some(_)) => 70,
+            (true, None) => 0,
+        }
+    }
+}
+
+/// Reads `[line N] Error...` and `[java line N] Error...`, for errors reported on another line
+/// than the comment's. Expectations for other implementations, like `[c line N]`, are ignored.
+fn explicit_line_error(comment: &str) -> Option<String> {
+    let rest = comment.strip_prefix('[')?;
+    let rest = rest.strip_prefix("java ").unwrap_or(rest);
+    let (line, error) = rest.strip_prefix("line ")?.split_once("] ")?;
+    if !error.starts_with("Error") || line.parse::<u32>().is_err() {
+        return None;
+    }
+
+    Some(format!("[line {}] {}", line, error))
+}
+
+/// The outcome of running one script.
+#[derive(Debug)]
+pub struct TestResult {
+    pub path: PathBuf,
+    pub expectations: usize,
+    /// Why the script failed; empty when it passed.
+    pub failures: Vec<String>,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// The results of a whole test directory, in path order.
+#[derive(Debug, Default)]
+pub struct TestSummary {
+    pub results: Vec<TestResult>,
+}
+
+impl TestSummary {
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|result| result.passed()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.passed()
+    }
+}
+
+impl fmt::Display for TestSummary {
+    /// Lists every failure, then the totals the way the Crafting Interpreters test runner does.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for result in self.results.iter().filter(|result| !result.passed()) {
+            writeln!(f, "FAIL {}", result.path.display())?;
+            for failure in result.failures.iter() {
+                writeln!(f, "     {}", failure)?;
+            }
+            writeln!(f)?;
+        }
+
+        match self.failed() {
+            0 => {
+                let expectations: usize = self.results.iter().map(|r| r.expectations).sum();
+                write!(
+                    f,
+                    "All {} tests passed ({} expectations).",
+                    self.passed(),
+                    expectations
+                )
+            }
+            failed => write!(
+                f,
+                "{} tests passed. {} tests failed.",
+                self.passed(),
+                failed
+            ),
+        }
+    }
+}
+
+/// Runs every `.lox` script under `dir` and checks it against its annotations.
+pub fn run_tests(dir: &Path) -> io::Result<TestSummary> {
+    let mut paths = vec![];
+    collect_scripts(dir, &mut paths)?;
+    paths.sort();
+
+    let mut summary = TestSummary::default();
+    for path in paths {
+        if let Some(result) = run_test(&path)? {
+            summary.results.push(result);
+        }
+    }
+
+    Ok(summary)
+}
+
+fn collect_scripts(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_scripts(&path, paths)?;
+        } else if path.extension().is_some_and(|ext| ext == "lox") {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs one script, or returns `None` if it's marked `// nontest`.
+pub fn run_test(path: &Path) -> io::Result<Option<TestResult>> {
+    let source = fs::read_to_string(path)?;
+    let expected = Expectations::parse(&source);
+    if expected.skip {
+        return Ok(None);
+    }
+
+    let out = SharedBuffer::new();
+    let err = SharedBuffer::new();
+    let mut interpreter = Interpreter::new();
+    interpreter.set_output(out.clone());
+    interpreter.set_diagnostics(err.clone());
+    interpreter.set_file(&path.display().to_string());
+
+    // annotations are written against the short format, whatever the caller asked for
+    let (format, colour) = error_format();
+    set_error_format(ErrorFormat::Short, false);
+    let res = panic::catch_unwind(AssertUnwindSafe(|| interpreter.run_source(&source)));
+    set_error_format(format, colour);
+
+    let mut failures = vec![];
+    let exit_code = match res {
+        Ok(Ok(())) => 0,
+        Ok(Err(err)) => err.exit_code(),
+        Err(_) => {
+            failures.push(String::from("Interpreter panicked."));
+            101
+        }
+    };
+
+    check_output(&expected, &out.contents(), &mut failures);
+    check_errors(&expected, &err.contents(), &mut failures);
+    if exit_code != expected.exit_code() {
+        failures.push(format!(
+            "Expected exit code {} and got {}.",
+            expected.exit_code(),
+            exit_code
+        ));
+    }
+
+    Ok(Some(TestResult {
+        path: path.to_path_buf(),
+        expectations: expected.count(),
+        failures,
+    }))
+}
+
+fn check_output(expected: &Expectations, output: &str, failures: &mut Vec<String>) {
+    let lines: Vec<&str> = output.lines().collect();
+    for (i, line) in lines.iter().enumerate() {
+        match expected.output.get(i) {
+            Some(value) if value == line => {}
+            Some(value) => {
+                failures.push(format!("Expected output '{}' and got '{}'.", value, line))
+            }
+            None => failures.push(format!("Got output '{}' when none was expected.", line)),
+        }
+    }
+
+    for value in expected.output.iter().skip(lines.len()) {
+        failures.push(format!("Missing expected output '{}'.", value));
+    }
+}
+
+fn check_errors(expected: &Expectations, errors: &str, failures: &mut Vec<String>) {
+    // warnings don't stop a script, so they're checked apart from its errors
+    let (warnings, lines): (Vec<&str>, Vec<&str>) = errors.lines().partition(|line| {
+        line.strip_prefix("[line ")
+            .and_then(|rest| rest.split_once("] "))
+            .is_some_and(|(_, text)| text.starts_with("Warning"))
+    });
+    for warning in expected.warnings.iter() {
+        if !warnings.contains(&warning.as_str()) {
+            failures.push(format!("Missing expected warning: {}", warning));
+        }
+    }
+
+    if let Some((message, line_num)) = &expected.runtime_error {
+        match lines.first() {
+            Some(line) if line == message => {}
+            Some(line) => failures.push(format!(
+                "Expected runtime error '{}' and got '{}'.",
+                message, line
+            )),
+            None => failures.push(format!(
+                "Expected runtime error '{}' and got none.",
+                message
+            )),
+        }
+
+        let trace = format!("[line {}]", line_num);
+        match lines.get(1) {
+            Some(line) if *line == trace => {}
+            Some(line) => failures.push(format!(
+                "Expected '{}' after the runtime error and got '{}'.",
+                trace, line
+            )),
+            None => failures.push(format!("Expected '{}' after the runtime error.", trace)),
+        }
+        return;
+    }
+
+    // compile errors can come in any order, but each one is expected exactly once
+    let mut missing: Vec<&String> = expected.compile_errors.iter().collect();
+    for line in lines {
+        match missing.iter().position(|error| *error == line) {
+            Some(i) => {
+                missing.remove(i);
+            }
+            None => failures.push(format!("Unexpected error: {}", line)),
+        }
+    }
+
+    for error in missing {
+        failures.push(format!("Missing expected error: {}", error));
+    }
+}
@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use crate::conformance::{run_test, run_tests, Expectations};
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("lox-{}-{}", name, std::process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn reads_annotations() {
+        let expectations = Expectations::parse(concat!(
+            "print 1; // expect: 1\n",
+            "print \"a // b\"; // expect: a // b\n",
+            "var = 2; // Error at '=': Expected variable name.\n",
+            "// [line 5] Error at end: Missing ';'.\n",
+            "// [c line 5] Error at end: Ignored.\n",
+            "print -\"a\"; // expect runtime error: Operand must be a number.\n",
+        ));
+        assert_eq!(expectations.output, vec!["1", "a // b"]);
+        assert_eq!(
+            expectations.compile_errors,
+            vec![
+                "[line 3] Error at '=': Expected variable name.",
+                "[line 5] Error at end: Missing ';'.",
+            ]
+        );
+        assert_eq!(
+            expectations.runtime_error,
+            Some((String::from("Operand must be a number."), 6))
+        );
+        assert_eq!(expectations.exit_code(), 65);
+    }
+
+    #[test]
+    fn checks_output_errors_and_exit_codes() {
+        let dir = test_dir("conformance");
+        fs::write(dir.join("pass.lox"), "print 1 + 2; // expect: 3\n").unwrap();
+        fs::write(
+            dir.join("nested/runtime.lox"),
+            "print \"a\";  // expect: a\nprint -\"a\"; // expect runtime error: Operand must be a number.\n",
+        )
+        .unwrap();
+        fs::write(dir.join("wrong.lox"), "print 1; // expect: 2\nprint 3;\n").unwrap();
+        fs::write(dir.join("helper.lox"), "// nontest\nprint 1;\n").unwrap();
+
+        let summary = run_tests(&dir).unwrap();
+        assert_eq!(summary.results.len(), 3);
+        assert_eq!((summary.passed(), summary.failed()), (2, 1));
+
+        let wrong = run_test(&dir.join("wrong.lox")).unwrap().unwrap();
+        assert_eq!(
+            wrong.failures,
+            vec![
+                "Expected output '2' and got '1'.",
+                "Got output '3' when none was expected.",
+            ]
+        );
+        assert!(summary
+            .to_string()
+            .ends_with("2 tests passed. 1 tests failed."));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn warnings_only_fail_when_annotated_and_missing() {
+        let dir = test_dir("conformance-warnings");
+        let unannotated = "match (1) { 1 => print 1; } // expect: 1\nprint -\"a\"; // expect runtime error: Operand must be a number.\n";
+        fs::write(dir.join("unannotated.lox"), unannotated).unwrap();
+        let annotated = "match (1) { // Warning at 'match': Match has no wildcard arm.\n1 => print 1; }\nmatch (1) { _ => {} } // Warning at 'match': Match has no wildcard arm.\n";
+        fs::write(dir.join("annotated.lox"), annotated).unwrap();
+
+        let passed = run_test(&dir.join("unannotated.lox")).unwrap().unwrap();
+        assert_eq!(passed.failures, Vec::<String>::new());
+
+        let failed = run_test(&dir.join("annotated.lox")).unwrap().unwrap();
+        assert_eq!(
+            failed.failures,
+            vec![
+                "Got output '1' when none was expected.",
+                "Missing expected warning: [line 3] Warning at 'match': Match has no wildcard arm.",
+            ]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,169 @@
+use crate::runner::{Hook, State};
+use crate::{Expr, Statement};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::rc::Rc;
+mod tests;
+
+/// How often a condition held and how often it didn't.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct BranchCounts {
+    pub taken: u64,
+    pub not_taken: u64,
+}
+
+/// Which statements and branches have run. Programs are registered up front, so lines and
+/// branches that never ran are reported with zero counts.
+#[derive(Default, Clone)]
+pub struct Coverage {
+    lines: BTreeMap<u32, u64>,
+    /// Keyed by the line and column where the condition starts, which tells apart several
+    /// conditions on one line.
+    branches: BTreeMap<(u32, u32), BranchCounts>,
+}
+
+impl Coverage {
+    pub fn line(&self, line: u32) -> Option<u64> {
+        self.lines.get(&line).copied()
+    }
+
+    /// The branches whose condition is on `line`.
+    pub fn branches(&self, line: u32) -> Vec<BranchCounts> {
+        self.branches
+            .range((line, 0)..(line + 1, 0))
+            .map(|(_, counts)| *counts)
+            .collect()
+    }
+
+    /// Registers every statement and condition in `program`.
+    pub fn add_program(&mut self, program: &[Expr]) {
+        for expr in program {
+            self.add_statement(expr);
+        }
+    }
+
+    fn add_statement(&mut self, expr: &Expr) {
+        let statement = match expr {
+            Expr::Scope(_, exprs) => return self.add_program(exprs),
+            Expr::Stmt(statement) => statement,
+            _ => {
+                self.lines.entry(expr.line()).or_default();
+                return;
+            }
+        };
+
+        self.lines.entry(statement.line()).or_default();
+        match statement {
+            Statement::IfStmt(conditionals) => {
+                for conditional in conditionals {
+                    if let Expr::Grouping(_) = *conditional.0 {
+                        self.add_branch(&conditional.0);
+                    }
+                    self.add_statement(&conditional.1);
+                }
+            }
+            Statement::WhileStmt(conditional) => {
+                self.add_branch(&conditional.0);
+                self.add_statement(&conditional.1);
+            }
+            Statement::MatchStmt(_, _, arms) => {
+                for arm in arms {
+                    if let Some(guard) = &arm.guard {
+                        self.add_branch(guard);
+                    }
+                    self.add_statement(&arm.body);
+                }
+            }
+            Statement::ForStmt(init, condition, update, body) => {
+                for expr in [init, update].into_iter().flatten() {
+                    self.add_statement(expr);
+                }
+                self.add_branch(condition);
+                self.add_statement(body);
+            }
+            Statement::ExportStmt(declaration) => self.add_statement(declaration),
+            _ => {}
+        }
+    }
+
+    fn add_branch(&mut self, condition: &Expr) {
+        self.branches.entry(condition.position()).or_default();
+    }
+
+    /// An lcov tracefile for `source_file`.
+    pub fn lcov(&self, source_file: &str) -> String {
+        let mut lcov = format!("TN:\nSF:{}\n", source_file);
+        let mut block = 0;
+        let mut prev_line = 0;
+        for ((line, _), counts) in self.branches.iter() {
+            block = if *line == prev_line { block + 1 } else { 0 };
+            prev_line = *line;
+            for (branch, count) in [counts.taken, counts.not_taken].into_iter().enumerate() {
+                // lcov writes '-' for a branch whose condition never ran
+                let count = match counts.taken + counts.not_taken {
+                    0 => String::from("-"),
+                    _ => count.to_string(),
+                };
+                let _ = writeln!(lcov, "BRDA:{},{},{},{}", line, block, branch, count);
+            }
+        }
+
+        let branches_hit = self
+            .branches
+            .values()
+            .flat_map(|counts| [counts.taken, counts.not_taken])
+            .filter(|count| *count > 0)
+            .count();
+        let _ = writeln!(
+            lcov,
+            "BRF:{}\nBRH:{}",
+            self.branches.len() * 2,
+            branches_hit
+        );
+
+        for (line, count) in self.lines.iter() {
+            let _ = writeln!(lcov, "DA:{},{}", line, count);
+        }
+        let lines_hit = self.lines.values().filter(|count| **count > 0).count();
+        let _ = writeln!(lcov, "LF:{}\nLH:{}", self.lines.len(), lines_hit);
+        lcov.push_str("end_of_record\n");
+
+        lcov
+    }
+}
+
+/// Records `Coverage` as statements run.
+pub struct CoverageRecorder {
+    coverage: Rc<RefCell<Coverage>>,
+}
+
+impl CoverageRecorder {
+    pub fn new(coverage: Rc<RefCell<Coverage>>) -> Self {
+        CoverageRecorder { coverage }
+    }
+}
+
+impl Hook for CoverageRecorder {
+    fn before_statement(&mut self, _: &mut State, expr: &Expr, _: usize) -> Result<(), ()> {
+        if !matches!(expr, Expr::Scope(..)) {
+            *self
+                .coverage
+                .borrow_mut()
+                .lines
+                .entry(expr.line())
+                .or_default() += 1;
+        }
+
+        Ok(())
+    }
+
+    fn branch(&mut self, condition: &Expr, taken: bool) {
+        let mut coverage = self.coverage.borrow_mut();
+        let counts = coverage.branches.entry(condition.position()).or_default();
+        match taken {
+            true => counts.taken += 1,
+            false => counts.not_taken += 1,
+        }
+    }
+}
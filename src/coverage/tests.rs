@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use crate::{BranchCounts, Interpreter, SharedBuffer};
+
+    const SOURCE: &str = "var x = 3;
+if (x > 5) {
+  print \"big\";
+} else {
+  print \"small\";
+}
+while (x > 0) x = x - 1;
+for (var i = 0; i < 2; i = i + 1) {}";
+
+    fn coverage() -> crate::Coverage {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(SharedBuffer::new());
+        interpreter.enable_coverage();
+        interpreter.run_source(SOURCE).unwrap();
+        interpreter.coverage().unwrap()
+    }
+
+    #[test]
+    fn tracks_lines_and_branches() {
+        let coverage = coverage();
+        assert_eq!(coverage.line(1), Some(1));
+        assert_eq!(coverage.line(3), Some(0));
+        assert_eq!(coverage.line(5), Some(1));
+        assert_eq!(
+            coverage.branches(2),
+            [BranchCounts {
+                taken: 0,
+                not_taken: 1
+            }]
+        );
+        assert_eq!(
+            coverage.branches(7),
+            [BranchCounts {
+                taken: 3,
+                not_taken: 1
+            }]
+        );
+        assert_eq!(
+            coverage.branches(8),
+            [BranchCounts {
+                taken: 2,
+                not_taken: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn writes_lcov_tracefile() {
+        let lcov = coverage().lcov("/tmp/script.lox");
+        assert!(lcov.starts_with("TN:\nSF:/tmp/script.lox\nBRDA:2,0,0,0\nBRDA:2,0,1,1\n"));
+        assert!(lcov.contains("BRF:6\nBRH:5\n"));
+        assert!(lcov.contains("DA:3,0\n"));
+        assert!(lcov.ends_with("LF:6\nLH:5\nend_of_record\n"));
+    }
+
+    #[test]
+    fn identical_conditions_on_one_line_are_separate_branches() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(SharedBuffer::new());
+        interpreter.enable_coverage();
+        interpreter
+            .run_source("var x = true;\nif (x) print 1; x = false; if (x) print 2;")
+            .unwrap();
+        let coverage = interpreter.coverage().unwrap();
+        assert_eq!(
+            coverage.branches(2),
+            [
+                BranchCounts {
+                    taken: 1,
+                    not_taken: 0
+                },
+                BranchCounts {
+                    taken: 0,
+                    not_taken: 1
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use crate::evaluate::Eval;
+use crate::interpreter::parse_expression;
+use crate::output::{errln, outln};
+use crate::runner::{Hook, State};
+use crate::Expr;
+use std::collections::HashSet;
+use std::io::BufRead;
+mod tests;
+
+const HELP: &str = "Commands:
+  break <line>, b <line>     set a breakpoint
+  delete <line>, d <line>    remove a breakpoint
+  continue, c                run until the next breakpoint
+  step, s                    run to the next statement, entering blocks
+  next, n                    run to the next statement at this level or above
+  out, o                     run until the current block is left
+  print <expr>, p <expr>     evaluate an expression in the current scope
+  scopes                     list every open scope and its variables
+  list, l                    show the source around the current line
+  quit, q                    stop the program";
+
+/// How far to run before pausing again.
+enum Mode {
+    Continue,
+    Step,
+    /// Pause at the next statement nested no deeper than this.
+    Next(usize),
+    /// Pause at the next statement nested shallower than this.
+    Out(usize),
+}
+
+/// Pauses the program before statements and reads commands from `input`, for the `debug` command.
+pub struct Debugger {
+    lines: Vec<String>,
+    input: Box<dyn BufRead>,
+    breakpoints: HashSet<u32>,
+    mode: Mode,
+    /// Line and depth of the previous statement, so a statement nested on the same line as its
+    /// parent doesn't hit a breakpoint twice.
+    last: (u32, usize),
+    quit: bool,
+}
+
+impl Debugger {
+    /// Starts paused at the first statement, so breakpoints can be set before anything runs.
+    pub fn new(source: &str, input: Box<dyn BufRead>) -> Self {
+        Debugger {
+            lines: source.lines().map(String::from).collect(),
+            input,
+            breakpoints: HashSet::new(),
+            mode: Mode::Step,
+            last: (0, 0),
+            quit: false,
+        }
+    }
+
+    fn should_pause(&self, line: u32, depth: usize) -> bool {
+        match self.mode {
+            Mode::Step => return true,
+            Mode::Next(level) if depth <= level => return true,
+            Mode::Out(level) if depth < level => return true,
+            _ => {}
+        }
+
+        let (last_line, last_depth) = self.last;
+        self.breakpoints.contains(&line) && !(line == last_line && depth > last_depth)
+    }
+
+    fn pause(&mut self, state: &mut State, line: u32, depth: usize) -> Result<(), ()> {
+        outln!("Paused at line {}:", line);
+        self.list(line, 0);
+
+        loop {
+            let mut command = String::new();
+            match self.input.read_line(&mut command) {
+                Ok(0) | Err(_) => {
+                    // Nothing left to read, so let the rest of the program run uninterrupted.
+                    self.breakpoints.clear();
+                    self.mode = Mode::Continue;
+                    return Ok(());
+                }
+                Ok(_) => {}
+            }
+
+            let command = command.trim();
+            let (name, arg) = match command.split_once(char::is_whitespace) {
+                Some((name, arg)) => (name, arg.trim()),
+                None => (command, ""),
+            };
+
+            match name {
+                "" => {}
+                "continue" | "c" => {
+                    self.mode = Mode::Continue;
+                    return Ok(());
+                }
+                "step" | "s" => {
+                    self.mode = Mode::Step;
+                    return Ok(());
+                }
+                "next" | "n" => {
+                    self.mode = Mode::Next(depth);
+                    return Ok(());
+                }
+                "out" | "o" => {
+                    self.mode = Mode::Out(depth);
+                    return Ok(());
+                }
+                "break" | "b" => {
+                    if let Some(line) = self.parse_line(arg) {
+                        self.breakpoints.insert(line);
+                        outln!("Breakpoint set at line {}.", line);
+                    }
+                }
+                "delete" | "d" => {
+                    if let Some(line) = self.parse_line(arg) {
+                        if self.breakpoints.remove(&line) {
+                            outln!("Breakpoint removed from line {}.", line);
+                        } else {
+                            errln!("No breakpoint at line {}.", line);
+                        }
+                    }
+                }
+                "print" | "p" => self.print(state, arg),
+                "scopes" => Self::scopes(state),
+                "list" | "l" => self.list(line, 2),
+                "quit" | "q" => {
+                    self.quit = true;
+                    return Err(());
+                }
+                "help" | "h" => outln!("{}", HELP),
+                _ => errln!(
+                    "Unknown command '{}'. Type 'help' for a list of commands.",
+                    name
+                ),
+            }
+        }
+    }
+
+    fn parse_line(&self, arg: &str) -> Option<u32> {
+        match arg.parse::<u32>() {
+            Ok(line) if line >= 1 && line as usize <= self.lines.len() => Some(line),
+            _ => {
+                errln!("Expected a line number between 1 and {}.", self.lines.len());
+                None
+            }
+        }
+    }
+
+    fn print(&self, state: &mut State, source: &str) {
+        if source.is_empty() {
+            errln!("Expected an expression to print.");
+            return;
+        }
+
+        // Parse and runtime errors have already been reported; the program stays paused.
+        if let Ok(expr) = parse_expression(source) {
+            if let Ok(value) = state.evaluate(expr) {
+                outln!("{}", value.value_string());
+            }
+        }
+    }
+
+    fn scopes(state: &State) {
+        for (depth, scope) in state.scope_variables().iter().enumerate().rev() {
+            match depth {
+                0 => outln!("#0 globals"),
+                _ => outln!("#{} block", depth),
+            }
+
+            for (name, value) in scope {
+                outln!("  {} = {}", name, value.value_string());
+            }
+        }
+    }
+
+    /// Shows `context` lines either side of `line`, marking the current one.
+    fn list(&self, line: u32, context: u32) {
+        let first = line.saturating_sub(context).max(1);
+        let last = (line + context).min(self.lines.len() as u32);
+        for num in first..=last {
+            let marker = if num == line { '>' } else { ' ' };
+            outln!("{} {:>4} | {}", marker, num, self.lines[num as usize - 1]);
+        }
+    }
+}
+
+impl Hook for Debugger {
+    fn before_statement(&mut self, state: &mut State, expr: &Expr, depth: usize) -> Result<(), ()> {
+        // Blocks are stepped through rather than paused on; their first statement is paused on.
+        if let Expr::Scope(..) = expr {
+            return Ok(());
+        }
+
+        let line = expr.line();
+        let res = match self.should_pause(line, depth) {
+            true => self.pause(state, line, depth),
+            false => Ok(()),
+        };
+        self.last = (line, depth);
+
+        res
+    }
+
+    fn stopped(&self) -> bool {
+        self.quit
+    }
+}
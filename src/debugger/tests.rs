@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod tests {
+    use crate::{Interpreter, SharedBuffer};
+    use std::io::Cursor;
+
+    const SOURCE: &str = "var a = 1;
+{
+  var b = a + 1;
+  print b;
+}
+print a;";
+
+    fn debug(commands: &str) -> (String, bool) {
+        let out = SharedBuffer::new();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(out.clone());
+        interpreter.set_diagnostics(SharedBuffer::new());
+
+        let res = interpreter.debug_source(SOURCE, Cursor::new(commands.to_string()));
+        (out.contents(), res.is_ok())
+    }
+
+    #[test]
+    fn pauses_at_first_statement_and_breakpoints() {
+        let (out, ok) = debug("b 4\nc\np b * 10\nc\n");
+        assert!(ok);
+        assert_eq!(
+            out,
+            "Paused at line 1:\n\
+             >    1 | var a = 1;\n\
+             Breakpoint set at line 4.\n\
+             Paused at line 4:\n\
+             >    4 |   print b;\n\
+             20\n\
+             2\n\
+             1\n"
+        );
+    }
+
+    fn paused_lines(commands: &str) -> Vec<String> {
+        let (out, _) = debug(commands);
+        out.lines()
+            .filter_map(|line| line.strip_prefix("Paused at line "))
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn step_next_and_out() {
+        assert_eq!(paused_lines("s\nn\no\n"), ["1:", "3:", "4:", "6:"]);
+        // stepping over the block runs it without pausing inside
+        assert_eq!(paused_lines("n\nn\n"), ["1:", "6:"]);
+    }
+
+    #[test]
+    fn lists_scopes_innermost_first() {
+        let (out, _) = debug("b 4\nc\nscopes\nc\n");
+        assert!(out.contains("#1 block\n  b = 2\n#0 globals\n  a = 1\n"));
+    }
+
+    #[test]
+    fn quitting_stops_the_program_cleanly() {
+        let (out, ok) = debug("q\n");
+        assert!(ok);
+        assert_eq!(out, "Paused at line 1:\n>    1 | var a = 1;\n");
+    }
+}
@@ -0,0 +1,346 @@
+use crate::json::Json;
+use crate::output::write_diagnostic_text;
+use crate::Token;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+mod tests;
+
+/// How syntax and runtime errors are written.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ErrorFormat {
+    /// One line per error, e.g. `[line 3] Error at 'x': Missing ';'.`
+    #[default]
+    Short,
+    /// The error with the offending source line and a caret under the exact span.
+    Rich,
+    /// One JSON object per line.
+    Json,
+}
+
+impl ErrorFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "short" => Some(ErrorFormat::Short),
+            "rich" => Some(ErrorFormat::Rich),
+            "json" => Some(ErrorFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+struct Source {
+    name: Option<String>,
+    lines: Vec<String>,
+}
+
+thread_local! {
+    static FORMAT: Cell<ErrorFormat> = const { Cell::new(ErrorFormat::Short) };
+    static COLOUR: Cell<bool> = const { Cell::new(false) };
+    static SOURCE: RefCell<Option<Rc<Source>>> = const { RefCell::new(None) };
+}
+
+/// Chooses how errors on this thread are written. Colour only applies to the rich format.
+pub fn set_error_format(format: ErrorFormat, colour: bool) {
+    FORMAT.with(|cell| cell.set(format));
+    COLOUR.with(|cell| cell.set(colour));
+}
+
+/// The format and colour setting errors are currently written with.
+pub(crate) fn error_format() -> (ErrorFormat, bool) {
+    (FORMAT.with(Cell::get), COLOUR.with(Cell::get))
+}
+
+/// Makes `text` the source that diagnostics quote until the returned guard is dropped.
+pub fn use_source(name: Option<String>, text: &str) -> SourceGuard {
+    let source = Source {
+        name,
+        lines: text.lines().map(String::from).collect(),
+    };
+
+    SourceGuard {
+        previous: SOURCE.with(|cell| cell.replace(Some(Rc::new(source)))),
+    }
+}
+
+/// Restores the source that was quoted before, e.g. once an imported module has run.
+pub struct SourceGuard {
+    previous: Option<Rc<Source>>,
+}
+
+impl Drop for SourceGuard {
+    fn drop(&mut self) {
+        SOURCE.with(|cell| cell.replace(self.previous.take()));
+    }
+}
+
+/// A span of source that a diagnostic points at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub line: u32,
+    /// 1-based; 0 when the span is unknown, which marks the whole line.
+    pub column: u32,
+    pub len: u32,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(token: &Token, message: &str) -> Self {
+        Label {
+            line: token.line_num,
+            column: token.column,
+            len: token.lexeme.chars().count().max(1) as u32,
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Severity {
+    Error,
+    /// Written like an error, but doesn't stop the program from running.
+    Warning,
+}
+
+impl Severity {
+    fn name(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+
+    /// Terminal colour code of the heading and the primary label.
+    fn colour(&self) -> &'static str {
+        match self {
+            Severity::Error => "1;31",
+            Severity::Warning => "1;33",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    severity: Severity,
+    /// What the short format prints, kept exactly as the interpreter has always written it.
+    short: String,
+    message: String,
+    /// The primary label first, then any secondary ones.
+    labels: Vec<Label>,
+}
+
+impl Diagnostic {
+    pub fn new(short: String, message: &str, primary: Label) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            short,
+            message: message.trim_end_matches('.').to_string(),
+            labels: vec![primary],
+        }
+    }
+
+    /// A parse error at `token`.
+    pub fn syntax(token: &Token, message: &str) -> Self {
+        let short = format!(
+            "[line {}] Error at '{}': {}.",
+            token.line_num, token.lexeme, message
+        );
+        Diagnostic::new(short, message, Label::new(token, ""))
+    }
+
+    /// A problem the parser noticed in a program it still accepts.
+    pub fn warning(token: &Token, message: &str) -> Self {
+        let short = format!(
+            "[line {}] Warning at '{}': {}.",
+            token.line_num, token.lexeme, message
+        );
+        let mut diagnostic = Diagnostic::new(short, message, Label::new(token, ""));
+        diagnostic.severity = Severity::Warning;
+
+        diagnostic
+    }
+
+    /// A runtime error raised at `token`; `message` ends with its own punctuation.
+    pub fn runtime(token: &Token, message: &str) -> Self {
+        let short = format!("{}\n[line {}]", message, token.line_num);
+        Diagnostic::new(short, message, Label::new(token, ""))
+    }
+
+    /// Adds a secondary label, such as where an unclosed block was opened.
+    pub fn with_label(mut self, token: &Token, message: &str) -> Self {
+        self.labels.push(Label::new(token, message));
+        self
+    }
+
+    pub fn render(&self, format: ErrorFormat) -> String {
+        let source = SOURCE.with(|cell| cell.borrow().clone());
+        let labels: Vec<Resolved> = self
+            .labels
+            .iter()
+            .map(|label| Resolved::new(label, source.as_deref()))
+            .collect();
+
+        match format {
+            ErrorFormat::Short => self.short.clone(),
+            ErrorFormat::Rich => {
+                let colour = COLOUR.with(Cell::get);
+                self.rich(&labels, source.as_deref(), colour)
+            }
+            ErrorFormat::Json => self.json(&labels, source.as_deref()).to_string(),
+        }
+    }
+
+    fn rich(&self, labels: &[Resolved], source: Option<&Source>, colour: bool) -> String {
+        let paint = |code: &str, text: &str| match colour {
+            true => format!("\x1b[{}m{}\x1b[0m", code, text),
+            false => text.to_string(),
+        };
+
+        let severity = self.severity;
+        let heading = paint(severity.colour(), severity.name());
+        let mut text = format!("{}: {}", heading, self.message);
+        let primary = &labels[0];
+        let width = labels
+            .iter()
+            .map(|label| label.line.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let pad = " ".repeat(width);
+        let name = source.and_then(|source| source.name.as_deref());
+        let location = match name {
+            Some(name) => format!("{}:{}:{}", name, primary.line, primary.column),
+            None => format!("{}:{}", primary.line, primary.column),
+        };
+        text.push_str(&format!("\n{}{} {}", pad, paint("1;34", "-->"), location));
+
+        let Some(source) = source else {
+            return text;
+        };
+        let gutter = paint("1;34", "|");
+        for (i, label) in labels.iter().enumerate() {
+            let Some(line) = source.lines.get(label.line as usize - 1) else {
+                continue;
+            };
+
+            // keep tabs so the marks line up with the text above them
+            let indent: String = line
+                .chars()
+                .take(label.column as usize - 1)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let (mark, code) = match i {
+                0 => ("^", severity.colour()),
+                _ => ("-", "1;34"),
+            };
+            let marks = mark.repeat(label.len as usize);
+            let underline = match label.message.is_empty() {
+                true => paint(code, &marks),
+                false => paint(code, &format!("{} {}", marks, label.message)),
+            };
+
+            text.push_str(&format!("\n{} {}", pad, gutter));
+            text.push_str(&format!(
+                "\n{} {} {}",
+                paint("1;34", &format!("{:>width$}", label.line)),
+                gutter,
+                line
+            ));
+            text.push_str(&format!("\n{} {} {}{}", pad, gutter, indent, underline));
+        }
+
+        text
+    }
+
+    fn json(&self, labels: &[Resolved], source: Option<&Source>) -> Json {
+        let primary = &labels[0];
+        let labels = labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| {
+                Json::object(vec![
+                    ("line", Json::from(label.line)),
+                    ("column", Json::from(label.column)),
+                    ("length", Json::from(label.len)),
+                    ("message", Json::from(label.message)),
+                    ("primary", Json::from(i == 0)),
+                ])
+            })
+            .collect();
+
+        Json::object(vec![
+            ("severity", Json::from(self.severity.name())),
+            ("message", Json::from(self.message.as_str())),
+            (
+                "file",
+                Json::from(source.and_then(|source| source.name.clone())),
+            ),
+            ("line", Json::from(primary.line)),
+            ("column", Json::from(primary.column)),
+            ("labels", Json::Array(labels)),
+        ])
+    }
+}
+
+/// A label with the gaps in what the tokens know filled in from the source: the end of file
+/// token has no line, and values made at runtime have no column.
+struct Resolved<'a> {
+    line: u32,
+    column: u32,
+    len: u32,
+    message: &'a str,
+}
+
+impl<'a> Resolved<'a> {
+    fn new(label: &'a Label, source: Option<&Source>) -> Self {
+        let lines = source.map_or(&[][..], |source| &source.lines[..]);
+        let mut resolved = Resolved {
+            line: label.line,
+            column: label.column,
+            len: label.len,
+            message: &label.message,
+        };
+
+        if resolved.line == 0 {
+            resolved.line = lines.len().max(1) as u32;
+            let end = lines.last().map_or(0, |line| line.chars().count());
+            resolved.column = end as u32 + 1;
+            resolved.len = 1;
+        } else if resolved.column == 0 {
+            let line = lines
+                .get(label.line as usize - 1)
+                .map_or("", String::as_str);
+            let indent = line.chars().take_while(|c| c.is_whitespace()).count();
+            resolved.column = indent as u32 + 1;
+            resolved.len = line.trim().chars().count().max(1) as u32;
+        }
+
+        resolved
+    }
+}
+
+/// Writes `diagnostic` in the current error format.
+pub fn report(diagnostic: &Diagnostic) {
+    write_diagnostic_text(&diagnostic.render(FORMAT.with(Cell::get)));
+}
+
+/// Formats an error that has no location, for the messages not yet turned into diagnostics.
+pub fn plain(message: String) -> String {
+    match FORMAT.with(Cell::get) {
+        ErrorFormat::Short => message,
+        ErrorFormat::Rich => {
+            let error = match COLOUR.with(Cell::get) {
+                true => "\x1b[1;31merror\x1b[0m",
+                false => "error",
+            };
+            format!("{}: {}", error, message)
+        }
+        ErrorFormat::Json => Json::object(vec![
+            ("severity", Json::from("error")),
+            ("message", Json::from(message)),
+            ("file", Json::Null),
+            ("line", Json::Null),
+            ("column", Json::Null),
+            ("labels", Json::Array(vec![])),
+        ])
+        .to_string(),
+    }
+}
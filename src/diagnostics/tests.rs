@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::{set_error_format, ErrorFormat};
+    use crate::json::Json;
+    use crate::{Interpreter, SharedBuffer};
+
+    fn errors(format: ErrorFormat, source: &str) -> String {
+        set_error_format(format, false);
+        let err = SharedBuffer::new();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(SharedBuffer::new());
+        interpreter.set_diagnostics(err.clone());
+        assert!(interpreter.run_source(source).is_err());
+        err.contents()
+    }
+
+    #[test]
+    fn short_format_is_unchanged() {
+        assert_eq!(
+            errors(ErrorFormat::Short, "var x = 1;\nprint x +;"),
+            "[line 2] Error at ';': Unexpected token or missing expression.\n"
+        );
+    }
+
+    #[test]
+    fn rich_format_points_at_the_token() {
+        assert_eq!(
+            errors(ErrorFormat::Rich, "var x = 1;\nprint x +;"),
+            concat!(
+                "error: Unexpected token or missing expression\n",
+                " --> 2:10\n",
+                "  |\n",
+                "2 | print x +;\n",
+                "  |          ^\n",
+            )
+        );
+    }
+
+    #[test]
+    fn unclosed_blocks_label_the_opening_brace() {
+        let text = errors(ErrorFormat::Rich, "{\n  print 1;\n");
+        assert!(text.contains("1 | {\n  | - block opened here"), "{}", text);
+    }
+
+    #[test]
+    fn json_format_is_one_object_per_error() {
+        let text = errors(ErrorFormat::Json, "print 1 + \"a\";");
+        let json = Json::parse(text.trim()).unwrap();
+        assert_eq!(
+            json.get("message"),
+            Some(&"Operands must be two numbers or two strings".into())
+        );
+        assert_eq!(json.get("line"), Some(&Json::from(1u32)));
+        assert_eq!(json.get("column"), Some(&Json::from(9u32)));
+    }
+
+    #[test]
+    fn warnings_leave_the_program_running() {
+        set_error_format(ErrorFormat::Json, false);
+        let err = SharedBuffer::new();
+        let out = SharedBuffer::new();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(out.clone());
+        interpreter.set_diagnostics(err.clone());
+
+        assert!(interpreter
+            .run_source("match (1) { 2 => print 2; }\nprint 1;")
+            .is_ok());
+        assert_eq!(out.contents(), "1\n");
+        let json = Json::parse(err.contents().trim()).unwrap();
+        assert_eq!(json.get("severity"), Some(&"warning".into()));
+        assert_eq!(
+            json.get("message"),
+            Some(&"Match has no wildcard arm".into())
+        );
+    }
+
+    #[test]
+    fn runtime_errors_know_their_line() {
+        assert_eq!(
+            errors(ErrorFormat::Short, "\n\nprint -(\"a\" + \"b\");"),
+            "Operand must be a number.\n[line 3]\n"
+        );
+    }
+}
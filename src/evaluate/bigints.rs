@@ -0,0 +1,83 @@
+use crate::builtins;
+use crate::diagnostics::{report, Diagnostic};
+use crate::{BigInt, Token, TokenType};
+use std::cmp::Ordering;
+
+/// Whether either operand is a big integer, which makes the operation one on big integers.
+pub fn involved(left: &Token, right: &Token) -> bool {
+    left.token_type == TokenType::BIGINT || right.token_type == TokenType::BIGINT
+}
+
+/// Applies a binary operator when one operand is a big integer. The other may be a plain
+/// integer, but never a float: mixing them would silently lose the precision asked for.
+pub fn arithmetic(left: &Token, right: &Token, operator: &Token) -> Result<Token, ()> {
+    let (Some(left), Some(right)) = (value(left), value(right)) else {
+        let message = "Operands must be integers when one is a big integer.";
+        report(&Diagnostic::runtime(operator, message));
+        return Err(());
+    };
+
+    let res = match operator.token_type {
+        TokenType::PLUS => left + right,
+        TokenType::MINUS => left - right,
+        TokenType::STAR => left * right,
+        // a big integer has no fraction to keep, so `/` rounds down like `div`
+        TokenType::SLASH | TokenType::DIV | TokenType::PERCENT => {
+            let Some((quotient, remainder)) = left.div_rem_floor(&right) else {
+                report(&Diagnostic::runtime(operator, "Division by zero."));
+                return Err(());
+            };
+            match operator.token_type {
+                TokenType::PERCENT => remainder,
+                _ => quotient,
+            }
+        }
+        _ => {
+            let message = format!(
+                "Operator '{}' doesn't support big integers.",
+                operator.lexeme
+            );
+            report(&Diagnostic::runtime(operator, &message));
+            return Err(());
+        }
+    };
+
+    Ok(builtins::bigint(&res, operator.line_num))
+}
+
+/// Orders two numbers when at least one is a big integer. Integers compare exactly and floats
+/// by value; `None` if neither is a big integer or the other operand isn't a number.
+pub fn compare(left: &Token, right: &Token) -> Option<Ordering> {
+    if !involved(left, right) {
+        return None;
+    }
+
+    match (value(left), value(right)) {
+        (Some(left), Some(right)) => Some(left.cmp(&right)),
+        _ if is_numeric(left) && is_numeric(right) => {
+            let left = left.literal.parse::<f64>().ok()?;
+            left.partial_cmp(&right.literal.parse::<f64>().ok()?)
+        }
+        _ => None,
+    }
+}
+
+/// `-val` for a big integer.
+pub fn negate(val: &Token, operator: &Token) -> Result<Token, ()> {
+    match BigInt::parse(&val.literal) {
+        Some(val) => Ok(builtins::bigint(&-val, operator.line_num)),
+        None => Err(()),
+    }
+}
+
+fn value(token: &Token) -> Option<BigInt> {
+    match token.token_type {
+        TokenType::BIGINT => BigInt::parse(&token.literal),
+        TokenType::INTEGER => token.literal.parse::<i64>().ok().map(BigInt::from),
+        _ => None,
+    }
+}
+
+fn is_numeric(token: &Token) -> bool {
+    token.token_type.is_number() || token.token_type == TokenType::BIGINT
+}
@@ -0,0 +1,148 @@
+use crate::builtins;
+use crate::diagnostics::{report, Diagnostic};
+use crate::{BigInt, Token, TokenType};
+
+/// Evaluates a number literal: without a decimal point it's an integer, otherwise a float. Whole
+/// numbers too large for an integer stay floats, as they were before integers existed. A big
+/// integer literal drops its `n` suffix, so it prints as just the digits.
+pub fn number_literal(mut token: Token) -> Token {
+    if token.token_type == TokenType::BIGINT {
+        token.lexeme = token.literal.clone();
+        return token;
+    }
+    if token.token_type != TokenType::NUMBER || token.lexeme.contains('.') {
+        return token;
+    }
+
+    match token.lexeme.parse::<i64>() {
+        Ok(val) => {
+            let mut integer = builtins::integer(val, token.line_num);
+            integer.column = token.column;
+            integer
+        }
+        Err(_) => token,
+    }
+}
+
+/// The values of two integer operands, or `None` if either is a float or not a number.
+pub fn operands(left: &Token, right: &Token) -> Option<(i64, i64)> {
+    if left.token_type != TokenType::INTEGER || right.token_type != TokenType::INTEGER {
+        return None;
+    }
+
+    Some((left.literal.parse().ok()?, right.literal.parse().ok()?))
+}
+
+/// Applies a binary operator to two integers. Returns `None` when the result isn't an integer,
+/// i.e. for `/` and for `**` with a negative exponent, so the caller uses floats instead.
+pub fn arithmetic(left: i64, right: i64, operator: &Token) -> Option<Result<Token, ()>> {
+    let res = match operator.token_type {
+        TokenType::PLUS => left.checked_add(right),
+        TokenType::MINUS => left.checked_sub(right),
+        TokenType::STAR => left.checked_mul(right),
+        TokenType::PERCENT | TokenType::DIV if right == 0 => {
+            report(&Diagnostic::runtime(operator, "Division by zero."));
+            return Some(Err(()));
+        }
+        TokenType::PERCENT => Some(floored_rem(left, right)),
+        // rounds down, so that `(a div b) * b + a % b == a`
+        TokenType::DIV => left.checked_div(right).map(|quotient| {
+            let rem = left % right;
+            match rem != 0 && (rem < 0) != (right < 0) {
+                true => quotient - 1,
+                false => quotient,
+            }
+        }),
+        TokenType::STAR_STAR if right < 0 => return None,
+        TokenType::STAR_STAR => u32::try_from(right)
+            .ok()
+            .and_then(|exponent| left.checked_pow(exponent)),
+        TokenType::PLUS_PERCENT => Some(left.wrapping_add(right)),
+        TokenType::MINUS_PERCENT => Some(left.wrapping_sub(right)),
+        TokenType::STAR_PERCENT => Some(left.wrapping_mul(right)),
+        TokenType::AMPERSAND => Some(left & right),
+        TokenType::PIPE => Some(left | right),
+        TokenType::CARET => Some(left ^ right),
+        TokenType::LESS_LESS | TokenType::GREATER_GREATER if !(0..64).contains(&right) => {
+            let message = "Shift amount must be between 0 and 63.";
+            report(&Diagnostic::runtime(operator, message));
+            return Some(Err(()));
+        }
+        TokenType::LESS_LESS => Some(left << right),
+        TokenType::GREATER_GREATER => Some(left >> right),
+        _ => return None,
+    };
+
+    Some(match res {
+        Some(val) => Ok(builtins::integer(val, operator.line_num)),
+        None => overflow(operator),
+    })
+}
+
+/// Compares two integers exactly, which floats can't do for large values.
+pub fn compare(left: i64, right: i64, operator: &Token) -> Option<bool> {
+    match operator.token_type {
+        TokenType::GREATER => Some(left > right),
+        TokenType::GREATER_EQUAL => Some(left >= right),
+        TokenType::LESS => Some(left < right),
+        TokenType::LESS_EQUAL => Some(left <= right),
+        _ => None,
+    }
+}
+
+/// `-val` and `~val`.
+pub fn unary(val: i64, operator: &Token) -> Result<Token, ()> {
+    let res = match operator.token_type {
+        TokenType::TILDE => Some(!val),
+        _ => val.checked_neg(),
+    };
+
+    match res {
+        Some(val) => Ok(builtins::integer(val, operator.line_num)),
+        None => overflow(operator),
+    }
+}
+
+/// Whether only integers are accepted by `operator`, which has no meaning for floats.
+pub fn is_integer_only(operator: TokenType) -> bool {
+    matches!(
+        operator,
+        TokenType::PLUS_PERCENT
+            | TokenType::MINUS_PERCENT
+            | TokenType::STAR_PERCENT
+            | TokenType::AMPERSAND
+            | TokenType::PIPE
+            | TokenType::CARET
+            | TokenType::TILDE
+            | TokenType::LESS_LESS
+            | TokenType::GREATER_GREATER
+    )
+}
+
+/// Whether numbers of two different types, such as an integer and a float, have exactly the same
+/// value. Maps hash numbers the same way, so equal keys always find each other.
+pub fn same_value(left: &Token, right: &Token) -> bool {
+    matches!((whole_value(left), whole_value(right)), (Some(left), Some(right)) if left == right)
+}
+
+fn whole_value(token: &Token) -> Option<BigInt> {
+    match token.token_type {
+        TokenType::NUMBER => BigInt::from_f64(token.literal.parse().ok()?),
+        TokenType::INTEGER | TokenType::BIGINT => BigInt::parse(&token.literal),
+        _ => None,
+    }
+}
+
+/// The remainder taking the sign of `right`, like `%` on floats.
+fn floored_rem(left: i64, right: i64) -> i64 {
+    let rem = left.wrapping_rem(right);
+    match rem != 0 && (rem < 0) != (right < 0) {
+        true => rem + right,
+        false => rem,
+    }
+}
+
+fn overflow(operator: &Token) -> Result<Token, ()> {
+    report(&Diagnostic::runtime(operator, "Integer overflow."));
+    Err(())
+}
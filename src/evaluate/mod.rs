@@ -1,15 +1,23 @@
-use codecrafters_interpreter::{Expr, Token, TokenType};
+use crate::builtins;
+use crate::diagnostics::{report, Diagnostic};
+use crate::output::errln;
+use crate::{Expr, LoxMap, Statement, Token, TokenType};
+use std::cell::RefCell;
+use std::rc::Rc;
+mod bigints;
+mod integers;
 mod tests;
 
-pub struct Stateless;
-
-impl Eval for Stateless {}
+pub use integers::number_literal;
 
 pub trait Eval {
     fn evaluate(&mut self, expr: Expr) -> Result<Token, ()> {
         let res: Token;
         match expr {
-            Expr::Literal(token) => res = token,
+            Expr::Literal(token) => match builtins::constant(&token.lexeme, token.line_num) {
+                Some(constant) if token.token_type == TokenType::IDENTIFIER => res = constant,
+                _ => res = number_literal(token),
+            },
             Expr::Unary(operator, val) => res = Self::eval_unary_expr(self, operator, *val)?,
             Expr::Grouping(expr) => res = Self::evaluate(self, *expr)?,
             Expr::Binary(left_expr, operator, right_expr) => {
@@ -20,12 +28,167 @@ pub trait Eval {
                 TokenType::AND => res = Self::eval_logical_and_expr(self, *left_expr, *right_expr)?,
                 _ => panic!("this shouldn't happen"),
             },
+            Expr::Ternary(condition, _, then, otherwise) => {
+                res = Self::eval_ternary_expr(self, *condition, *then, *otherwise)?;
+            }
+            Expr::Map(brace, entries) => res = Self::eval_map_expr(self, brace, entries)?,
+            Expr::Index(target, bracket, key) => {
+                res = Self::eval_index_expr(self, *target, bracket, *key)?;
+            }
+            Expr::Call(callee, args) => res = Self::eval_call_expr(self, callee, args)?,
+            Expr::Stmt(Statement::IndexAssignmentStmt(target, bracket, key, value)) => {
+                res = Self::eval_index_assignment(self, *target, bracket, *key, *value)?;
+            }
             _ => res = Token::new(TokenType::INVALID, String::new(), String::new(), 0),
         }
 
         Ok(res)
     }
 
+    fn eval_map_expr(&mut self, brace: Token, entries: Vec<(Expr, Expr)>) -> Result<Token, ()> {
+        let mut map = LoxMap::new();
+        for (key_expr, value_expr) in entries {
+            let key = Self::evaluate(self, key_expr)?;
+            builtins::hash_check(&brace, &key)?;
+            let value = Self::evaluate(self, value_expr)?;
+            map.insert(key, value);
+        }
+
+        Ok(Token::new_map(map, brace.line_num))
+    }
+
+    fn eval_index_expr(&mut self, target: Expr, bracket: Token, key: Expr) -> Result<Token, ()> {
+        let target = Self::evaluate(self, target)?;
+        let key = Self::evaluate(self, key)?;
+        if target.token_type == TokenType::STRING {
+            return builtins::index_string(&bracket, &target, &key);
+        }
+
+        let map = Self::index_target(&target, &bracket)?;
+        builtins::hash_check(&bracket, &key)?;
+        let value = map.borrow().get(&key);
+        match value {
+            Some(token) => Ok(token),
+            None => {
+                let message = format!("Undefined key '{}'.", key.value_string());
+                report(&Diagnostic::runtime(&bracket, &message));
+                Err(())
+            }
+        }
+    }
+
+    fn eval_index_assignment(
+        &mut self,
+        target: Expr,
+        bracket: Token,
+        key: Expr,
+        value: Expr,
+    ) -> Result<Token, ()> {
+        let target = Self::evaluate(self, target)?;
+        let key = Self::evaluate(self, key)?;
+        if target.token_type == TokenType::STRING {
+            report(&Diagnostic::runtime(&bracket, "Strings are immutable."));
+            return Err(());
+        }
+
+        let map = Self::index_target(&target, &bracket)?;
+        builtins::hash_check(&bracket, &key)?;
+        let value = Self::evaluate(self, value)?;
+        map.borrow_mut().insert(key, value.clone());
+
+        Ok(value)
+    }
+
+    /// Applies a compound assignment or increment to `target[key]`. Returns the stored value, or
+    /// the previous one for a postfix increment.
+    fn eval_index_update(
+        &mut self,
+        target: Expr,
+        bracket: Token,
+        key: Expr,
+        operator: Token,
+        value: Option<Expr>,
+        postfix: bool,
+    ) -> Result<Token, ()> {
+        let target = Self::evaluate(self, target)?;
+        let key = Self::evaluate(self, key)?;
+        if target.token_type == TokenType::STRING {
+            report(&Diagnostic::runtime(&bracket, "Strings are immutable."));
+            return Err(());
+        }
+
+        let map = Self::index_target(&target, &bracket)?;
+        builtins::hash_check(&bracket, &key)?;
+        let Some(old) = map.borrow().get(&key) else {
+            let message = format!("Undefined key '{}'.", key.value_string());
+            report(&Diagnostic::runtime(&bracket, &message));
+            return Err(());
+        };
+
+        let new = Self::eval_update(self, old.clone(), &operator, value)?;
+        map.borrow_mut().insert(key, new.clone());
+
+        Ok(if postfix { old } else { new })
+    }
+
+    /// The value a compound assignment stores: `old` combined with `value`, or with 1 for `++`
+    /// and `--`.
+    fn eval_update(
+        &mut self,
+        old: Token,
+        operator: &Token,
+        value: Option<Expr>,
+    ) -> Result<Token, ()> {
+        let value = match value {
+            Some(value) => Self::evaluate(self, value)?,
+            None if !old.token_type.is_number() && old.token_type != TokenType::BIGINT => {
+                report(&Diagnostic::runtime(operator, "Operand must be a number."));
+                return Err(());
+            }
+            None => builtins::integer(1, operator.line_num),
+        };
+
+        let mut arithmetic = operator.clone();
+        arithmetic.token_type = operator
+            .token_type
+            .arithmetic_operator()
+            .expect("the parser only builds updates from arithmetic operators");
+        Self::eval_arithmetic_op(old, value, &arithmetic)
+    }
+
+    fn eval_call_expr(&mut self, callee: Token, arg_exprs: Vec<Expr>) -> Result<Token, ()> {
+        let mut args: Vec<Token> = vec![];
+        for arg in arg_exprs {
+            args.push(Self::evaluate(self, arg)?);
+        }
+
+        match Self::call_builtin(self, &callee, args) {
+            Some(res) => res,
+            None => {
+                let message = format!("Undefined function '{}'.", callee.lexeme);
+                report(&Diagnostic::runtime(&callee, &message));
+                Err(())
+            }
+        }
+    }
+
+    /// Dispatches a call to a built-in. Evaluators with their own state (e.g. the random number
+    /// generator) override this and fall back to `builtins::call`.
+    fn call_builtin(&mut self, callee: &Token, args: Vec<Token>) -> Option<Result<Token, ()>> {
+        builtins::call(callee, args)
+    }
+
+    fn index_target(target: &Token, bracket: &Token) -> Result<Rc<RefCell<LoxMap>>, ()> {
+        match target.as_map() {
+            Some(map) => Ok(map),
+            None => {
+                let message = "Only maps and strings can be indexed.";
+                report(&Diagnostic::runtime(bracket, message));
+                Err(())
+            }
+        }
+    }
+
     fn eval_logical_or_expr(&mut self, left_expr: Expr, right_expr: Expr) -> Result<Token, ()> {
         let token: Token;
         let left_val = Self::evaluate(self, left_expr)?;
@@ -50,13 +213,46 @@ pub trait Eval {
         Ok(token)
     }
 
+    /// Evaluates only the branch the condition picks.
+    fn eval_ternary_expr(
+        &mut self,
+        condition: Expr,
+        then: Expr,
+        otherwise: Expr,
+    ) -> Result<Token, ()> {
+        let condition = Self::evaluate(self, condition)?;
+        match Self::get_bool(condition)? {
+            true => Self::evaluate(self, then),
+            false => Self::evaluate(self, otherwise),
+        }
+    }
+
     fn eval_unary_expr(&mut self, operator: Token, val: Expr) -> Result<Token, ()> {
-        let mut token = Token::new(TokenType::INVALID, String::new(), String::new(), 0);
+        let mut token = Token::new(
+            TokenType::INVALID,
+            String::new(),
+            String::new(),
+            operator.line_num,
+        );
         let right = Self::evaluate(self, val)?;
         match operator.token_type {
+            TokenType::MINUS | TokenType::TILDE if right.token_type == TokenType::INTEGER => {
+                let val = right.literal.parse::<i64>().unwrap();
+                return integers::unary(val, &operator);
+            }
+            TokenType::MINUS if right.token_type == TokenType::BIGINT => {
+                return bigints::negate(&right, &operator);
+            }
+            TokenType::TILDE => {
+                report(&Diagnostic::runtime(
+                    &operator,
+                    "Operand must be an integer.",
+                ));
+                return Err(());
+            }
             TokenType::MINUS => {
                 if right.token_type != TokenType::NUMBER {
-                    eprintln!("Operand must be a number.\n[line {}]", right.line_num);
+                    report(&Diagnostic::runtime(&operator, "Operand must be a number."));
                     return Err(());
                 }
 
@@ -103,17 +299,25 @@ pub trait Eval {
         let token: Token;
         let left = Self::evaluate(self, left_expr)?;
         let right = Self::evaluate(self, right_expr)?;
-        let operator_type = operator.token_type;
-        match operator_type {
-            TokenType::PLUS | TokenType::MINUS | TokenType::STAR | TokenType::SLASH => {
-                token = Self::eval_arithmetic_op(left, right, operator_type)?;
+        match operator.token_type {
+            TokenType::PLUS
+            | TokenType::MINUS
+            | TokenType::STAR
+            | TokenType::SLASH
+            | TokenType::PERCENT
+            | TokenType::DIV
+            | TokenType::STAR_STAR => {
+                token = Self::eval_arithmetic_op(left, right, &operator)?;
+            }
+            operator_type if integers::is_integer_only(operator_type) => {
+                token = Self::eval_arithmetic_op(left, right, &operator)?;
             }
             TokenType::GREATER_EQUAL
             | TokenType::GREATER
             | TokenType::LESS
             | TokenType::LESS_EQUAL
             | TokenType::EQUAL_EQUAL
-            | TokenType::BANG_EQUAL => token = Self::eval_comparison(left, right, operator_type)?,
+            | TokenType::BANG_EQUAL => token = Self::eval_comparison(left, right, &operator)?,
             _ => {
                 panic!("this shouldn't happen");
             }
@@ -125,25 +329,43 @@ pub trait Eval {
     fn eval_arithmetic_op(
         left_token: Token,
         right_token: Token,
-        operator_type: TokenType,
+        operator: &Token,
     ) -> Result<Token, ()> {
+        if bigints::involved(&left_token, &right_token) {
+            return bigints::arithmetic(&left_token, &right_token, operator);
+        }
+
+        // integers stay integers where they can, otherwise both operands are used as floats
+        if let Some((left, right)) = integers::operands(&left_token, &right_token) {
+            if let Some(res) = integers::arithmetic(left, right, operator) {
+                return res;
+            }
+        }
+
         let token: Token;
-        match operator_type {
+        match operator.token_type {
             TokenType::PLUS => {
                 let str_type = TokenType::STRING;
                 if left_token.token_type == str_type && right_token.token_type == str_type {
-                    token = Self::concat_strings(left_token, right_token);
-                } else if Self::num_check(left_token.token_type, right_token.token_type) {
-                    token = Self::add(left_token, right_token);
+                    token = Self::concat_strings(left_token, right_token, operator);
+                } else if left_token.token_type.is_number() && right_token.token_type.is_number() {
+                    token = Self::add(left_token, right_token, operator);
                 } else {
-                    eprintln!("Operands must be two numbers or two strings.");
+                    let message = "Operands must be two numbers or two strings.";
+                    report(&Diagnostic::runtime(operator, message));
                     return Err(());
                 }
             }
-            TokenType::MINUS => token = Self::subtract(left_token, right_token)?,
-            TokenType::STAR => token = Self::multiply(left_token, right_token)?,
-            TokenType::SLASH => token = Self::divide(left_token, right_token)?,
-            _ => return Err(()),
+            TokenType::MINUS => token = Self::subtract(left_token, right_token, operator)?,
+            TokenType::STAR => token = Self::multiply(left_token, right_token, operator)?,
+            TokenType::SLASH => token = Self::divide(left_token, right_token, operator)?,
+            TokenType::PERCENT => token = Self::modulo(left_token, right_token, operator)?,
+            TokenType::DIV => token = Self::int_divide(left_token, right_token, operator)?,
+            TokenType::STAR_STAR => token = Self::power(left_token, right_token, operator)?,
+            _ => {
+                report(&Diagnostic::runtime(operator, "Operands must be integers."));
+                return Err(());
+            }
         }
 
         Ok(token)
@@ -152,41 +374,59 @@ pub trait Eval {
     fn eval_comparison(
         left_token: Token,
         right_token: Token,
-        operator_type: TokenType,
+        operator: &Token,
     ) -> Result<Token, ()> {
         let true_token = Ok(Token::new(
             TokenType::TRUE,
             String::from("true"),
             String::from("null"),
-            left_token.line_num,
+            operator.line_num,
         ));
         let false_token = Ok(Token::new(
             TokenType::FALSE,
             String::from("false"),
             String::from("null"),
-            left_token.line_num,
+            operator.line_num,
         ));
-        match operator_type {
+        if let Some(ordering) = bigints::compare(&left_token, &right_token) {
+            let res = match operator.token_type {
+                TokenType::GREATER => Some(ordering.is_gt()),
+                TokenType::GREATER_EQUAL => Some(ordering.is_ge()),
+                TokenType::LESS => Some(ordering.is_lt()),
+                TokenType::LESS_EQUAL => Some(ordering.is_le()),
+                _ => None,
+            };
+            match res {
+                Some(true) => return true_token,
+                Some(false) => return false_token,
+                None => {}
+            }
+        }
+        if let Some((left, right)) = integers::operands(&left_token, &right_token) {
+            match integers::compare(left, right, operator) {
+                Some(true) => return true_token,
+                Some(false) => return false_token,
+                None => {}
+            }
+        }
+
+        match operator.token_type {
             TokenType::EQUAL_EQUAL => {
-                if (left_token.token_type != right_token.token_type)
-                    || (left_token.lexeme != right_token.lexeme)
-                {
+                if !Self::values_equal(&left_token, &right_token) {
                     return false_token;
                 }
 
                 return true_token;
             }
             TokenType::BANG_EQUAL => {
-                if (left_token.token_type != right_token.token_type)
-                    || (left_token.lexeme != right_token.lexeme)
-                {
+                if !Self::values_equal(&left_token, &right_token) {
                     return true_token;
                 }
 
                 return false_token;
             }
             TokenType::GREATER_EQUAL => {
-                if !Self::num_check(left_token.token_type, right_token.token_type) {
+                if !Self::num_check(left_token.token_type, right_token.token_type, operator) {
                     return Err(());
                 }
 
@@ -198,7 +438,7 @@ pub trait Eval {
                 return false_token;
             }
             TokenType::GREATER => {
-                if !Self::num_check(left_token.token_type, right_token.token_type) {
+                if !Self::num_check(left_token.token_type, right_token.token_type, operator) {
                     return Err(());
                 }
 
@@ -210,7 +450,7 @@ pub trait Eval {
                 return false_token;
             }
             TokenType::LESS => {
-                if !Self::num_check(left_token.token_type, right_token.token_type) {
+                if !Self::num_check(left_token.token_type, right_token.token_type, operator) {
                     return Err(());
                 }
 
@@ -222,7 +462,7 @@ pub trait Eval {
                 return false_token;
             }
             TokenType::LESS_EQUAL => {
-                if !Self::num_check(left_token.token_type, right_token.token_type) {
+                if !Self::num_check(left_token.token_type, right_token.token_type, operator) {
                     return Err(());
                 }
 
@@ -237,10 +477,46 @@ pub trait Eval {
         }
     }
 
+    fn values_equal(left_token: &Token, right_token: &Token) -> bool {
+        let is_numeric =
+            |token: &Token| token.token_type.is_number() || token.token_type == TokenType::BIGINT;
+        if is_numeric(left_token)
+            && is_numeric(right_token)
+            && left_token.token_type != right_token.token_type
+        {
+            // an integer equals the float with exactly the same value
+            return integers::same_value(left_token, right_token);
+        }
+
+        if left_token.token_type == TokenType::NUMBER && right_token.token_type == TokenType::NUMBER
+        {
+            // `2.0` and `2.00`, or `0.0` and `-0.0`, are the same float
+            let left = left_token.literal.parse::<f64>();
+            let right = right_token.literal.parse::<f64>();
+            return matches!((left, right), (Ok(left), Ok(right)) if left == right);
+        }
+
+        if left_token.token_type != right_token.token_type {
+            return false;
+        }
+
+        match (left_token.as_map(), right_token.as_map()) {
+            // maps are compared by identity
+            (Some(left_map), Some(right_map)) => Rc::ptr_eq(&left_map, &right_map),
+            _ => left_token.lexeme == right_token.lexeme,
+        }
+    }
+
     fn get_bool(token: Token) -> Result<bool, ()> {
         let mut flag = false;
-        if matches!(token.token_type, TokenType::TRUE | TokenType::STRING)
-            || (token.token_type == TokenType::NUMBER && token.literal != "0")
+        if matches!(
+            token.token_type,
+            TokenType::TRUE
+                | TokenType::STRING
+                | TokenType::MAP
+                | TokenType::INTEGER
+                | TokenType::BIGINT
+        ) || (token.token_type == TokenType::NUMBER && token.literal != "0")
         {
             flag = true;
         } else if matches!(
@@ -248,23 +524,23 @@ pub trait Eval {
             TokenType::FALSE | TokenType::NIL | TokenType::NUMBER
         ) {
         } else {
-            eprintln!("[line {}] Invalid condition used.", token.line_num);
+            errln!("[line {}] Invalid condition used.", token.line_num);
             return Err(());
         }
 
         Ok(flag)
     }
 
-    fn concat_strings(str1_token: Token, str2_token: Token) -> Token {
+    fn concat_strings(str1_token: Token, str2_token: Token, operator: &Token) -> Token {
         Token::new(
             TokenType::STRING,
             format!("\"{}{}\"", str1_token.literal, str2_token.literal),
             format!("{}{}", str1_token.literal, str2_token.literal),
-            0,
+            operator.line_num,
         )
     }
 
-    fn add(val1_token: Token, val2_token: Token) -> Token {
+    fn add(val1_token: Token, val2_token: Token, operator: &Token) -> Token {
         let (num1, num2) = Self::parse_nums(val1_token.literal, val2_token.literal);
         let res = num1 + num2;
 
@@ -272,12 +548,12 @@ pub trait Eval {
             TokenType::NUMBER,
             Self::parse_lexeme(res.to_string()),
             Self::parse_literal(res.to_string()),
-            0,
+            operator.line_num,
         )
     }
 
-    fn subtract(val1_token: Token, val2_token: Token) -> Result<Token, ()> {
-        if !Self::num_check(val1_token.token_type, val2_token.token_type) {
+    fn subtract(val1_token: Token, val2_token: Token, operator: &Token) -> Result<Token, ()> {
+        if !Self::num_check(val1_token.token_type, val2_token.token_type, operator) {
             return Err(());
         }
 
@@ -288,12 +564,12 @@ pub trait Eval {
             TokenType::NUMBER,
             Self::parse_lexeme(res.to_string()),
             Self::parse_literal(res.to_string()),
-            0,
+            operator.line_num,
         ))
     }
 
-    fn multiply(val1_token: Token, val2_token: Token) -> Result<Token, ()> {
-        if !Self::num_check(val1_token.token_type, val2_token.token_type) {
+    fn multiply(val1_token: Token, val2_token: Token, operator: &Token) -> Result<Token, ()> {
+        if !Self::num_check(val1_token.token_type, val2_token.token_type, operator) {
             return Err(());
         }
 
@@ -304,12 +580,12 @@ pub trait Eval {
             TokenType::NUMBER,
             Self::parse_lexeme(res.to_string()),
             Self::parse_literal(res.to_string()),
-            0,
+            operator.line_num,
         ))
     }
 
-    fn divide(val1_token: Token, val2_token: Token) -> Result<Token, ()> {
-        if !Self::num_check(val1_token.token_type, val2_token.token_type) {
+    fn divide(val1_token: Token, val2_token: Token, operator: &Token) -> Result<Token, ()> {
+        if !Self::num_check(val1_token.token_type, val2_token.token_type, operator) {
             return Err(());
         }
 
@@ -320,10 +596,77 @@ pub trait Eval {
             TokenType::NUMBER,
             Self::parse_lexeme(res.to_string()),
             Self::parse_literal(res.to_string()),
-            0,
+            operator.line_num,
         ))
     }
 
+    /// `a % b` takes the sign of `b`, matching `div` so that `(a div b) * b + a % b == a`.
+    fn modulo(val1_token: Token, val2_token: Token, operator: &Token) -> Result<Token, ()> {
+        let (num1, num2) = Self::divisible_nums(val1_token, val2_token, operator)?;
+        let mut res = num1 % num2;
+        if res == 0.0 {
+            // `-5 % 2.5` leaves -0, which would print as "-0"
+            res = 0.0;
+        } else if (res < 0.0) != (num2 < 0.0) {
+            res += num2;
+        }
+
+        Ok(Token::new(
+            TokenType::NUMBER,
+            Self::parse_lexeme(res.to_string()),
+            Self::parse_literal(res.to_string()),
+            operator.line_num,
+        ))
+    }
+
+    /// Division rounding the quotient down, so `-7 div 2` is -4.
+    fn int_divide(val1_token: Token, val2_token: Token, operator: &Token) -> Result<Token, ()> {
+        let (num1, num2) = Self::divisible_nums(val1_token, val2_token, operator)?;
+        let res = (num1 / num2).floor();
+
+        Ok(Token::new(
+            TokenType::NUMBER,
+            Self::parse_lexeme(res.to_string()),
+            Self::parse_literal(res.to_string()),
+            operator.line_num,
+        ))
+    }
+
+    fn power(val1_token: Token, val2_token: Token, operator: &Token) -> Result<Token, ()> {
+        if !Self::num_check(val1_token.token_type, val2_token.token_type, operator) {
+            return Err(());
+        }
+
+        let (num1, num2) = Self::parse_nums(val1_token.literal, val2_token.literal);
+        let res = num1.powf(num2);
+
+        Ok(Token::new(
+            TokenType::NUMBER,
+            Self::parse_lexeme(res.to_string()),
+            Self::parse_literal(res.to_string()),
+            operator.line_num,
+        ))
+    }
+
+    /// The operands of `%` or `div`, which must be numbers with a non-zero divisor.
+    fn divisible_nums(
+        val1_token: Token,
+        val2_token: Token,
+        operator: &Token,
+    ) -> Result<(f32, f32), ()> {
+        if !Self::num_check(val1_token.token_type, val2_token.token_type, operator) {
+            return Err(());
+        }
+
+        let (num1, num2) = Self::parse_nums(val1_token.literal, val2_token.literal);
+        if num2 == 0.0 {
+            report(&Diagnostic::runtime(operator, "Division by zero."));
+            return Err(());
+        }
+
+        Ok((num1, num2))
+    }
+
     #[inline]
     fn parse_literal(val: String) -> String {
         match val.parse::<i32>() {
@@ -342,10 +685,9 @@ pub trait Eval {
         (val1.parse::<f32>().unwrap(), val2.parse::<f32>().unwrap())
     }
 
-    fn num_check(type1: TokenType, type2: TokenType) -> bool {
-        let num_type = TokenType::NUMBER;
-        if !(type1 == num_type && type2 == num_type) {
-            eprintln!("Operands must be numbers");
+    fn num_check(type1: TokenType, type2: TokenType, operator: &Token) -> bool {
+        if !(type1.is_number() && type2.is_number()) {
+            report(&Diagnostic::runtime(operator, "Operands must be numbers."));
             return false;
         }
 
@@ -1,7 +1,151 @@
 #[cfg(test)]
 mod tests {
+    use crate::{BigInt, Interpreter, SharedBuffer, Value};
+
     #[test]
-    fn test_fn() {
-        todo!();
+    fn evaluates_arithmetic_and_strings() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(
+            interpreter.eval_expr("(1 + 2) * 3 - 4 / 2"),
+            Ok(Value::Number(7.0))
+        );
+        assert_eq!(
+            interpreter.eval_expr("\"lo\" + \"x\""),
+            Ok(Value::String(String::from("lox")))
+        );
+        assert_eq!(
+            interpreter.eval_expr("1 < 2 == !nil"),
+            Ok(Value::Bool(true))
+        );
+    }
+
+    #[test]
+    fn ternaries_evaluate_only_the_chosen_branch() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(
+            interpreter.eval_expr("nil ? -\"a\" : 1 < 2 ? \"yes\" : -\"b\""),
+            Ok(Value::String(String::from("yes")))
+        );
+    }
+
+    #[test]
+    fn modulo_and_div_round_down() {
+        let mut interpreter = Interpreter::new();
+        for (source, expected) in [
+            ("-7.0 % 3.0", 2.0),
+            ("7.0 % -3.0", -2.0),
+            ("-7.0 div 2.0", -4.0),
+            ("(-7.0 div 2.0) * 2.0 + -7.0 % 2.0", -7.0),
+            ("2 ** 0.5 ** 2", 2f64.powf(0.25)),
+        ] {
+            match interpreter.eval_expr(source) {
+                Ok(Value::Number(n)) => assert!((n - expected).abs() < 1e-6, "{}", source),
+                other => panic!("{} gave {:?}", source, other),
+            }
+        }
+        assert!(interpreter.eval_expr("1 % 0").is_err());
+        match interpreter.eval_expr("-5 % 2.5") {
+            Ok(Value::Number(n)) => assert!(n == 0.0 && n.is_sign_positive(), "{}", n),
+            other => panic!("-5 % 2.5 gave {:?}", other),
+        }
+    }
+
+    #[test]
+    fn integers_stay_exact_until_mixed_with_floats() {
+        let mut interpreter = Interpreter::new();
+        for (source, expected) in [
+            ("-7 % 3", Value::Integer(2)),
+            ("-7 div 2", Value::Integer(-4)),
+            ("2 ** 62 + 1", Value::Integer((1 << 62) + 1)),
+            ("9223372036854775807 +% 1", Value::Integer(i64::MIN)),
+            ("~5", Value::Integer(-6)),
+            ("6 & 3 | 8 ^ 1", Value::Integer(11)),
+            ("1 << 4 >> 2", Value::Integer(4)),
+            ("99999999999999999999", Value::Number(1e20)),
+            ("7 / 2", Value::Number(3.5)),
+            ("1 + 0.5", Value::Number(1.5)),
+            ("3 == 3.0", Value::Bool(true)),
+        ] {
+            assert_eq!(interpreter.eval_expr(source), Ok(expected), "{}", source);
+        }
+
+        let err = SharedBuffer::new();
+        interpreter.set_diagnostics(err.clone());
+        assert!(interpreter.eval_expr("9223372036854775807 + 1").is_err());
+        assert!(interpreter.eval_expr("1.5 & 1").is_err());
+        assert!(interpreter.eval_expr("1 << 64").is_err());
+        assert_eq!(
+            err.contents(),
+            "Integer overflow.\n[line 1]\n\
+             Operands must be integers.\n[line 1]\n\
+             Shift amount must be between 0 and 63.\n[line 1]\n"
+        );
+    }
+
+    #[test]
+    fn map_keys_match_exactly_when_numbers_compare_equal() {
+        let mut interpreter = Interpreter::new();
+        for (left, right) in [
+            ("2", "2.0"),
+            ("0", "-0.0"),
+            ("0.0", "-0.0"),
+            ("2.0", "2.00"),
+            ("3n", "3.0"),
+            ("16777216", "16777217.0"),
+            ("16777217", "16777217.0"),
+            ("9007199254740993", "9007199254740992.0"),
+            ("1", "1.5"),
+        ] {
+            let equal = interpreter.eval_expr(&format!("{} == {}", left, right));
+            let found = interpreter.eval_expr(&format!("has({{{}: 1}}, {})", left, right));
+            assert_eq!(equal, found, "{} and {}", left, right);
+        }
+    }
+
+    #[test]
+    fn big_integers_never_overflow() {
+        let mut interpreter = Interpreter::new();
+        let big = |text: &str| Value::BigInt(BigInt::parse(text).unwrap());
+        for (source, expected) in [
+            ("9223372036854775807n + 1", big("9223372036854775808")),
+            (
+                "-(2n * 3) * 4611686018427387904",
+                big("-27670116110564327424"),
+            ),
+            ("-7n / 2", big("-4")),
+            ("-7n % 3", big("2")),
+            (
+                "100000000000000000000n < 100000000000000000001n",
+                Value::Bool(true),
+            ),
+            ("3n == 3", Value::Bool(true)),
+            ("3n >= 3.5", Value::Bool(false)),
+        ] {
+            assert_eq!(interpreter.eval_expr(source), Ok(expected), "{}", source);
+        }
+
+        let err = SharedBuffer::new();
+        interpreter.set_diagnostics(err.clone());
+        assert!(interpreter.eval_expr("1n + 0.5").is_err());
+        assert!(interpreter.eval_expr("1n % 0").is_err());
+        assert!(interpreter.eval_expr("1n & 1").is_err());
+        assert_eq!(
+            err.contents(),
+            "Operands must be integers when one is a big integer.\n[line 1]\n\
+             Division by zero.\n[line 1]\n\
+             Operator '&' doesn't support big integers.\n[line 1]\n"
+        );
+    }
+
+    #[test]
+    fn operand_errors_report_the_operator_line() {
+        let err = SharedBuffer::new();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_diagnostics(err.clone());
+
+        assert!(interpreter
+            .run_source("var a = 1;\nprint a\n  * \"b\";")
+            .is_err());
+        assert_eq!(err.contents(), "Operands must be numbers.\n[line 3]\n");
     }
 }
@@ -0,0 +1,144 @@
+use crate::output::{SharedBuffer, Sinks};
+use crate::tokenizer::{tokenize_lossless, Trivia, TriviaKind};
+use crate::{Token, TokenType};
+use std::cell::RefCell;
+use std::rc::Rc;
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HighlightFormat {
+    /// Terminal escape codes.
+    Ansi,
+    /// A `<pre>` block with a CSS class on every token.
+    Html,
+}
+
+impl HighlightFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ansi" => Some(HighlightFormat::Ansi),
+            "html" => Some(HighlightFormat::Html),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Category {
+    Keyword,
+    Identifier,
+    String,
+    Number,
+    Operator,
+    Punctuation,
+    Comment,
+    /// Whitespace, and characters the tokenizer rejected.
+    Plain,
+}
+
+impl Category {
+    fn of(token: &Token) -> Self {
+        match token.token_type {
+            TokenType::STRING => Category::String,
+            TokenType::NUMBER | TokenType::BIGINT => Category::Number,
+            TokenType::IDENTIFIER => Category::Identifier,
+            t if t.is_keyword() => Category::Keyword,
+            t if t.is_operator() => Category::Operator,
+            _ => Category::Punctuation,
+        }
+    }
+
+    fn class(&self) -> Option<&'static str> {
+        match self {
+            Category::Keyword => Some("lox-keyword"),
+            Category::Identifier => Some("lox-identifier"),
+            Category::String => Some("lox-string"),
+            Category::Number => Some("lox-number"),
+            Category::Operator => Some("lox-operator"),
+            Category::Punctuation => Some("lox-punctuation"),
+            Category::Comment => Some("lox-comment"),
+            Category::Plain => None,
+        }
+    }
+
+    fn ansi(&self) -> Option<&'static str> {
+        match self {
+            Category::Keyword => Some("\x1b[35m"),
+            Category::String => Some("\x1b[32m"),
+            Category::Number => Some("\x1b[36m"),
+            Category::Operator => Some("\x1b[33m"),
+            Category::Comment => Some("\x1b[90m"),
+            Category::Identifier | Category::Punctuation | Category::Plain => None,
+        }
+    }
+}
+
+const STYLE: &str = "<style>
+.lox { background: #fafafa; color: #383a42; padding: 0.5em; }
+.lox-keyword { color: #a626a4; font-weight: bold; }
+.lox-string { color: #50a14f; }
+.lox-number { color: #0184bc; }
+.lox-operator { color: #c18401; }
+.lox-comment { color: #a0a1a7; font-style: italic; }
+</style>
+";
+
+/// Renders `source` with every token coloured by category. The output keeps comments and
+/// whitespace, so stripping the markup gives back the source unchanged.
+pub fn highlight(source: &str, format: HighlightFormat) -> String {
+    let spans = spans(source);
+    match format {
+        HighlightFormat::Ansi => spans
+            .iter()
+            .map(|(category, text)| match category.ansi() {
+                Some(code) => format!("{}{}\x1b[0m", code, text),
+                None => text.to_string(),
+            })
+            .collect(),
+        HighlightFormat::Html => {
+            let code: String = spans
+                .iter()
+                .map(|(category, text)| match category.class() {
+                    Some(class) => format!("<span class=\"{}\">{}</span>", class, escape(text)),
+                    None => escape(text),
+                })
+                .collect();
+            format!("{}<pre class=\"lox\"><code>{}</code></pre>\n", STYLE, code)
+        }
+    }
+}
+
+/// Splits `source` into consecutive spans covering all of it.
+fn spans(source: &str) -> Vec<(Category, String)> {
+    // errors in the source shouldn't stop it from being shown
+    let buffer = SharedBuffer::new();
+    let sinks = Sinks {
+        output: None,
+        diagnostics: Some(Rc::new(RefCell::new(buffer))),
+    };
+    let guard = sinks.install();
+    let (tokens, _) = tokenize_lossless(source);
+    drop(guard);
+
+    let trivia = |trivia: Trivia| match trivia.kind {
+        TriviaKind::Comment => (Category::Comment, trivia.text),
+        _ => (Category::Plain, trivia.text),
+    };
+    let mut spans = vec![];
+    for token in tokens {
+        spans.extend(token.leading.into_iter().map(trivia));
+        if token.token.token_type != TokenType::EOF {
+            spans.push((Category::of(&token.token), token.token.lexeme));
+        }
+        spans.extend(token.trailing.into_iter().map(trivia));
+    }
+
+    spans
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use crate::highlight::{highlight, HighlightFormat};
+
+    fn strip_ansi(text: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|c| *c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
+
+        plain
+    }
+
+    #[test]
+    fn ansi_keeps_comments_and_whitespace() {
+        let source = "// greet\nvar name = \"lox\";\n\tprint name  >= 2; // trailing\n";
+        let output = highlight(source, HighlightFormat::Ansi);
+        assert_eq!(strip_ansi(&output), source);
+        assert!(output.starts_with("\x1b[90m// greet\x1b[0m\n\x1b[35mvar\x1b[0m name "));
+        assert!(output.contains("\x1b[32m\"lox\"\x1b[0m;"));
+        assert!(output.contains("\x1b[33m>=\x1b[0m \x1b[36m2\x1b[0m"));
+    }
+
+    #[test]
+    fn html_tags_tokens_with_classes() {
+        let output = highlight("if (a < 1) print \"<b>\"; // done", HighlightFormat::Html);
+        let code = output
+            .split_once("<pre class=\"lox\"><code>")
+            .unwrap()
+            .1
+            .strip_suffix("</code></pre>\n")
+            .unwrap();
+        assert_eq!(
+            code,
+            concat!(
+                "<span class=\"lox-keyword\">if</span> ",
+                "<span class=\"lox-punctuation\">(</span>",
+                "<span class=\"lox-identifier\">a</span> ",
+                "<span class=\"lox-operator\">&lt;</span> ",
+                "<span class=\"lox-number\">1</span>",
+                "<span class=\"lox-punctuation\">)</span> ",
+                "<span class=\"lox-keyword\">print</span> ",
+                "<span class=\"lox-string\">&quot;&lt;b&gt;&quot;</span>",
+                "<span class=\"lox-punctuation\">;</span> ",
+                "<span class=\"lox-comment\">// done</span>",
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_source_is_still_shown() {
+        let source = "var a = 1 @ 2;\nprint \"open";
+        let output = highlight(source, HighlightFormat::Ansi);
+        assert_eq!(strip_ansi(&output), source);
+        assert!(output.contains(" @ \x1b[36m2\x1b[0m"));
+    }
+}
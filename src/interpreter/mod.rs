@@ -0,0 +1,284 @@
+use crate::coverage::{Coverage, CoverageRecorder};
+use crate::debugger::Debugger;
+use crate::diagnostics::{report, use_source, Diagnostic, Label};
+use crate::evaluate::Eval;
+use crate::lint::{LintConfig, LintWarning};
+use crate::output::Sinks;
+use crate::parser::Parser;
+use crate::profiler::{Profile, Profiler};
+use crate::runner::{Limits, State};
+use crate::tokenizer::tokenize;
+use crate::typecheck::check;
+use crate::Expr;
+use std::cell::RefCell;
+use std::io::{BufRead, Write};
+use std::rc::Rc;
+use thiserror::Error;
+mod tests;
+mod value;
+
+pub use value::Value;
+
+/// A function provided by the embedding program and callable from scripts by name.
+pub type HostFunction = Box<dyn FnMut(&[Value]) -> Result<Value, String>>;
+
+/// Why a script failed. The diagnostics themselves have already been written to stderr.
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("invalid interpreter configuration")]
+    Config,
+    #[error("syntax error")]
+    Compile,
+    #[error("runtime error")]
+    Runtime,
+    #[error("execution limit exceeded")]
+    LimitExceeded,
+}
+
+impl Error {
+    /// The process exit code the `interpreter` binary uses for this error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Config => 64,
+            Error::Compile => 65,
+            Error::Runtime => 70,
+            Error::LimitExceeded => 75,
+        }
+    }
+}
+
+/// Tokenizes and parses a whole program.
+pub fn parse(source: &str) -> Result<Vec<Expr>, Error> {
+    let _source = use_source(None, source);
+    parse_program(source)
+}
+
+fn parse_program(source: &str) -> Result<Vec<Expr>, Error> {
+    let (tokens, status_code) = tokenize(source.to_string());
+    if status_code != 0 {
+        return Err(Error::Compile);
+    }
+
+    let mut parser = Parser::new(tokens);
+    let expressions = parser.parse().map_err(|_| Error::Compile)?;
+    for warning in parser.warnings() {
+        report(warning);
+    }
+
+    Ok(expressions)
+}
+
+/// Parses a single expression, as used by the `parse` and `evaluate` commands.
+pub fn parse_expression(source: &str) -> Result<Expr, Error> {
+    let _source = use_source(None, source);
+    let (tokens, _) = tokenize(source.to_string());
+    let mut parser = Parser::new(tokens);
+
+    parser.parse_assignment().map_err(|_| Error::Compile)
+}
+
+/// Parses a program and checks it against its type annotations, reporting every mismatch.
+pub fn typecheck(source: &str) -> Result<(), Error> {
+    let _source = use_source(None, source);
+    let expressions = parse_program(source)?;
+    let errors = check(&expressions);
+    for error in errors.iter() {
+        let position = Label {
+            line: error.line,
+            column: error.column,
+            len: 1,
+            message: String::new(),
+        };
+        report(&Diagnostic::new(
+            error.to_string(),
+            &error.message,
+            position,
+        ));
+    }
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(Error::Compile),
+    }
+}
+
+/// Parses a program and returns the warnings of the lint rules enabled in `config`.
+pub fn lint(source: &str, config: &LintConfig) -> Result<Vec<LintWarning>, Error> {
+    let expressions = parse(source)?;
+    Ok(crate::lint::lint(&expressions, source, config))
+}
+
+/// An interpreter whose globals persist across calls, for embedding Lox in other programs.
+pub struct Interpreter {
+    state: State,
+    sinks: Sinks,
+    profile: Option<Rc<RefCell<Profile>>>,
+    coverage: Option<Rc<RefCell<Coverage>>>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Interpreter {
+            state: State::new(),
+            sinks: Sinks::default(),
+            profile: None,
+            coverage: None,
+        }
+    }
+
+    /// Sends `print` output to `writer` instead of stdout.
+    pub fn set_output<W: Write + 'static>(&mut self, writer: W) {
+        self.sinks.output = Some(Rc::new(RefCell::new(writer)));
+    }
+
+    /// Sends syntax and runtime error messages to `writer` instead of stderr.
+    pub fn set_diagnostics<W: Write + 'static>(&mut self, writer: W) {
+        self.sinks.diagnostics = Some(Rc::new(RefCell::new(writer)));
+    }
+
+    /// Runs a program. Globals it declares stay visible to later calls.
+    pub fn run_source(&mut self, source: &str) -> Result<(), Error> {
+        let _guard = self.sinks.install();
+        let _source = use_source(self.state.file_name(), source);
+        let expressions = parse_program(source)?;
+        if let Some(coverage) = &self.coverage {
+            coverage.borrow_mut().add_program(&expressions);
+        }
+
+        self.state.start_budget();
+        if self.state.run(expressions).is_err() {
+            return Err(self.runtime_err());
+        }
+
+        Ok(())
+    }
+
+    /// Runs a program under the debugger, which reads its commands from `input`. Quitting the
+    /// debugger stops the program without an error.
+    pub fn debug_source<R: BufRead + 'static>(
+        &mut self,
+        source: &str,
+        input: R,
+    ) -> Result<(), Error> {
+        let _guard = self.sinks.install();
+        let _source = use_source(self.state.file_name(), source);
+        let expressions = parse_program(source)?;
+        self.state.start_budget();
+        let hooks = self.state.take_hooks();
+        self.state
+            .add_hook(Box::new(Debugger::new(source, Box::new(input))));
+        let res = self.state.run(expressions);
+        let stopped = self.state.take_hooks().iter().any(|hook| hook.stopped());
+        for hook in hooks {
+            self.state.add_hook(hook);
+        }
+        if res.is_err() && !stopped {
+            return Err(self.runtime_err());
+        }
+
+        self.state.recover();
+        Ok(())
+    }
+
+    /// Evaluates a single expression against the current globals.
+    pub fn eval_expr(&mut self, source: &str) -> Result<Value, Error> {
+        let _guard = self.sinks.install();
+        let _source = use_source(self.state.file_name(), source);
+        let expr = parse_expression(source)?;
+        self.state.start_budget();
+        match self.state.evaluate(expr) {
+            Ok(token) => Ok(Value::from_token(&token)),
+            Err(_) => Err(self.runtime_err()),
+        }
+    }
+
+    fn runtime_err(&mut self) -> Error {
+        self.state.recover();
+        if self.state.limit_exceeded() {
+            return Error::LimitExceeded;
+        }
+
+        Error::Runtime
+    }
+
+    /// Sets the step, nesting depth and time budgets applied to each `run_source`/`eval_expr`.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.state.set_limits(limits);
+    }
+
+    /// Starts recording per-line timings and folded stacks for later runs.
+    pub fn enable_profiling(&mut self) {
+        if self.profile.is_none() {
+            let profile = Rc::new(RefCell::new(Profile::default()));
+            self.state
+                .add_hook(Box::new(Profiler::new(profile.clone())));
+            self.profile = Some(profile);
+        }
+    }
+
+    /// What has been recorded since profiling was enabled.
+    pub fn profile(&self) -> Option<Profile> {
+        self.profile
+            .as_ref()
+            .map(|profile| profile.borrow().clone())
+    }
+
+    /// Starts recording which lines and branches later runs execute.
+    pub fn enable_coverage(&mut self) {
+        if self.coverage.is_none() {
+            let coverage = Rc::new(RefCell::new(Coverage::default()));
+            self.state
+                .add_hook(Box::new(CoverageRecorder::new(coverage.clone())));
+            self.coverage = Some(coverage);
+        }
+    }
+
+    /// The lines and branches of every program run since coverage was enabled.
+    pub fn coverage(&self) -> Option<Coverage> {
+        self.coverage
+            .as_ref()
+            .map(|coverage| coverage.borrow().clone())
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.state.get_global(name).map(Value::from_token)
+    }
+
+    pub fn set_global(&mut self, name: &str, value: Value) {
+        self.state.set_global(name, value.to_token(0));
+    }
+
+    /// Makes `function` callable from scripts as `name(...)`. Host functions take precedence over
+    /// built-ins of the same name, and an `Err` becomes a Lox runtime error.
+    pub fn register_fn<F>(&mut self, name: &str, function: F)
+    where
+        F: FnMut(&[Value]) -> Result<Value, String> + 'static,
+    {
+        self.state.register_host_fn(name, Box::new(function));
+    }
+
+    /// Records the script's path so `import` can resolve modules relative to it.
+    pub fn set_file(&mut self, filename: &str) {
+        self.state.set_file(filename);
+    }
+
+    pub fn seed_random(&mut self, seed: u64) {
+        self.state.seed_random(seed);
+    }
+
+    pub fn allow_read(&mut self, dir: &str) -> Result<(), Error> {
+        let _guard = self.sinks.install();
+        self.state.allow_read(dir).map_err(|_| Error::Config)
+    }
+
+    pub fn allow_write(&mut self, dir: &str) -> Result<(), Error> {
+        let _guard = self.sinks.install();
+        self.state.allow_write(dir).map_err(|_| Error::Config)
+    }
+}
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use crate::{Error, Interpreter, Limits, SharedBuffer, Value};
+    use std::time::Duration;
+
+    #[test]
+    fn globals_persist_between_runs() {
+        let mut interpreter = Interpreter::new();
+        interpreter.run_source("var count = 1;").unwrap();
+        interpreter.run_source("count = count + 2;").unwrap();
+
+        assert_eq!(interpreter.get_global("count"), Some(Value::Number(3.0)));
+        assert_eq!(interpreter.eval_expr("count * 2"), Ok(Value::Number(6.0)));
+    }
+
+    #[test]
+    fn host_values_are_visible_to_scripts() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_global("name", Value::String(String::from("lox")));
+        interpreter.register_fn("shout", |args| match args {
+            [Value::String(val)] => Ok(Value::String(val.to_uppercase())),
+            _ => Err(String::from("shout expects a string.")),
+        });
+
+        interpreter.run_source("var loud = shout(name);").unwrap();
+        assert_eq!(
+            interpreter.get_global("loud"),
+            Some(Value::String(String::from("LOX")))
+        );
+        assert_eq!(interpreter.run_source("shout(1);"), Err(Error::Runtime));
+    }
+
+    #[test]
+    fn errors_map_to_exit_codes() {
+        let mut interpreter = Interpreter::new();
+        let err = interpreter.run_source("var = 1;").unwrap_err();
+        assert_eq!(err.exit_code(), 65);
+
+        let err = interpreter.run_source("{ print -\"a\"; }").unwrap_err();
+        assert_eq!(err.exit_code(), 70);
+
+        // the failed block's scope doesn't leak into the next run
+        interpreter.run_source("var after = true;").unwrap();
+        assert_eq!(interpreter.get_global("after"), Some(Value::Bool(true)));
+    }
+
+    #[test]
+    fn output_and_diagnostics_can_be_captured() {
+        let out = SharedBuffer::new();
+        let err = SharedBuffer::new();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(out.clone());
+        interpreter.set_diagnostics(err.clone());
+
+        interpreter
+            .run_source("print \"hi\"; print 1 + 2;")
+            .unwrap();
+        assert_eq!(out.contents(), "hi\n3\n");
+
+        assert!(interpreter.run_source("print missing;").is_err());
+        assert_eq!(err.contents(), "[line 1] Undeclared variable: 'missing'\n");
+
+        assert!(interpreter.run_source("print (1;").is_err());
+        assert!(err.contents().ends_with("Error at ';': Missing ')'.\n"));
+    }
+
+    #[test]
+    fn execution_limits_stop_runaway_scripts() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_diagnostics(SharedBuffer::new());
+        interpreter.set_limits(Limits {
+            max_steps: Some(100),
+            ..Limits::default()
+        });
+        let err = interpreter.run_source("while (true) {}").unwrap_err();
+        assert_eq!(err, Error::LimitExceeded);
+        assert_eq!(err.exit_code(), 75);
+
+        // the budget is per run, so a short script still runs afterwards
+        interpreter.run_source("var x = 1;").unwrap();
+
+        interpreter.set_limits(Limits {
+            timeout: Some(Duration::from_millis(50)),
+            ..Limits::default()
+        });
+        let err = interpreter.run_source("while (true) {}").unwrap_err();
+        assert_eq!(err, Error::LimitExceeded);
+    }
+
+    #[test]
+    fn nesting_depth_is_limited() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_diagnostics(SharedBuffer::new());
+        interpreter.set_limits(Limits {
+            max_depth: 20,
+            ..Limits::default()
+        });
+
+        let source = format!("1{}", " + 1".repeat(30));
+        assert_eq!(interpreter.eval_expr(&source), Err(Error::LimitExceeded));
+        assert_eq!(interpreter.eval_expr("1 + 1"), Ok(Value::Number(2.0)));
+    }
+}
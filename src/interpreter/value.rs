@@ -0,0 +1,114 @@
+use crate::builtins;
+use crate::{BigInt, LoxMap, Token, TokenType};
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+/// A Lox value as seen by code embedding the interpreter.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Nil,
+    Bool(bool),
+    Integer(i64),
+    BigInt(BigInt),
+    Number(f64),
+    String(String),
+    Map(Rc<RefCell<LoxMap>>),
+}
+
+impl Value {
+    pub fn from_token(token: &Token) -> Self {
+        match token.token_type {
+            TokenType::TRUE => Value::Bool(true),
+            TokenType::FALSE => Value::Bool(false),
+            TokenType::INTEGER => match token.literal.parse::<i64>() {
+                Ok(val) => Value::Integer(val),
+                Err(_) => Value::Nil,
+            },
+            TokenType::BIGINT => match BigInt::parse(&token.literal) {
+                Some(val) => Value::BigInt(val),
+                None => Value::Nil,
+            },
+            TokenType::NUMBER => Value::Number(token.literal.parse::<f64>().unwrap_or(f64::NAN)),
+            TokenType::STRING => Value::String(token.literal.clone()),
+            TokenType::MAP => match token.as_map() {
+                Some(map) => Value::Map(map),
+                None => Value::Nil,
+            },
+            _ => Value::Nil,
+        }
+    }
+
+    pub fn to_token(&self, line_num: u32) -> Token {
+        match self {
+            Value::Nil => Token::new(
+                TokenType::NIL,
+                String::from("nil"),
+                String::from("null"),
+                line_num,
+            ),
+            Value::Bool(true) => Token::new(
+                TokenType::TRUE,
+                String::from("true"),
+                String::from("null"),
+                line_num,
+            ),
+            Value::Bool(false) => Token::new(
+                TokenType::FALSE,
+                String::from("false"),
+                String::from("null"),
+                line_num,
+            ),
+            Value::Integer(val) => builtins::integer(*val, line_num),
+            Value::BigInt(val) => builtins::bigint(val, line_num),
+            Value::Number(val) => {
+                let mut literal = val.to_string();
+                if literal.parse::<i32>().is_ok() {
+                    literal = format!("{}.0", literal);
+                }
+
+                Token::new(TokenType::NUMBER, val.to_string(), literal, line_num)
+            }
+            Value::String(val) => Token::new(
+                TokenType::STRING,
+                format!("\"{}\"", val),
+                val.clone(),
+                line_num,
+            ),
+            Value::Map(map) => {
+                let mut token = Token::new(
+                    TokenType::MAP,
+                    String::from("<map>"),
+                    String::from("null"),
+                    line_num,
+                );
+                token.object = Some(crate::Object::Map(Rc::clone(map)));
+
+                token
+            }
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Bool(val1), Value::Bool(val2)) => val1 == val2,
+            (Value::Integer(val1), Value::Integer(val2)) => val1 == val2,
+            (Value::BigInt(val1), Value::BigInt(val2)) => val1 == val2,
+            (Value::Number(val1), Value::Number(val2)) => val1 == val2,
+            (Value::Integer(val1), Value::Number(val2))
+            | (Value::Number(val2), Value::Integer(val1)) => *val1 as f64 == *val2,
+            (Value::String(val1), Value::String(val2)) => val1 == val2,
+            (Value::Map(map1), Value::Map(map2)) => Rc::ptr_eq(map1, map2),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_token(0).value_string())
+    }
+}
@@ -0,0 +1,331 @@
+use std::fmt;
+mod tests;
+
+/// A JSON value, for the tooling commands that talk to other programs. Objects keep their keys
+/// in insertion order so output is stable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object(entries: Vec<(&str, Json)>) -> Json {
+        Json::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(val) => Some(*val),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Json>> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = JsonParser {
+            chars: text.chars().collect(),
+            pos: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos < parser.chars.len() {
+            return Err(format!("Unexpected trailing input at {}", parser.pos));
+        }
+
+        Ok(value)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(val: bool) -> Self {
+        Json::Bool(val)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(val: f64) -> Self {
+        Json::Number(val)
+    }
+}
+
+impl From<u32> for Json {
+    fn from(val: u32) -> Self {
+        Json::Number(val as f64)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(val: usize) -> Self {
+        Json::Number(val as f64)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(val: &str) -> Self {
+        Json::String(val.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(val: String) -> Self {
+        Json::String(val)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(items: Vec<T>) -> Self {
+        Json::Array(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(val: Option<T>) -> Self {
+        val.map_or(Json::Null, Into::into)
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(val) => write!(f, "{}", val),
+            Json::Number(val) if val.is_finite() => write!(f, "{}", val),
+            Json::Number(_) => write!(f, "null"),
+            Json::String(val) => write_string(f, val),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, val: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in val.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+struct JsonParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl JsonParser {
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some('t') => self.keyword("true", Json::Bool(true)),
+            Some('f') => self.keyword("false", Json::Bool(false)),
+            Some('n') => self.keyword("null", Json::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) => Err(format!("Unexpected '{}' at {}", c, self.pos)),
+            None => Err(String::from("Unexpected end of input")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        self.pos += 1;
+        let mut entries = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Json::Object(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(format!("Expected object key at {}", self.pos));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.value()?;
+            entries.push((key, value));
+
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(entries)),
+                _ => return Err(format!("Expected ',' or '}}' at {}", self.pos)),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        self.pos += 1;
+        let mut items = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Json::Array(items));
+        }
+
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(items)),
+                _ => return Err(format!("Expected ',' or ']' at {}", self.pos)),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let mut val = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(val),
+                Some('\\') => match self.next() {
+                    Some('n') => val.push('\n'),
+                    Some('r') => val.push('\r'),
+                    Some('t') => val.push('\t'),
+                    Some('b') => val.push('\u{8}'),
+                    Some('f') => val.push('\u{c}'),
+                    Some('u') => val.push(self.unicode_escape()?),
+                    Some(c) => val.push(c),
+                    None => return Err(String::from("Unterminated string")),
+                },
+                Some(c) => val.push(c),
+                None => return Err(String::from("Unterminated string")),
+            }
+        }
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| String::from("Invalid escape"));
+        }
+
+        // a surrogate pair spells out a character outside the basic plane
+        if self.next() != Some('\\') || self.next() != Some('u') {
+            return Err(String::from("Unpaired surrogate"));
+        }
+        let low = self.hex4()?;
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF))
+            .ok_or_else(|| String::from("Invalid escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits: String = (0..4).filter_map(|_| self.next()).collect();
+        u32::from_str_radix(&digits, 16).map_err(|_| format!("Invalid escape at {}", self.pos))
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if !(c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')) {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse::<f64>()
+            .map(Json::Number)
+            .map_err(|_| format!("Invalid number '{}'", text))
+    }
+
+    fn keyword(&mut self, word: &str, value: Json) -> Result<Json, String> {
+        for expected in word.chars() {
+            if self.next() != Some(expected) {
+                return Err(format!("Expected '{}' at {}", word, self.pos));
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(format!("Expected '{}' at {}", expected, self.pos)),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\n' | '\r' | '\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        if c.is_some() {
+            self.pos += 1;
+        }
+
+        c
+    }
+}
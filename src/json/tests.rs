@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod tests {
+    use crate::json::Json;
+
+    #[test]
+    fn parses_and_serializes_round_trip() {
+        let text =
+            r#"{"id":1,"name":"a \"quoted\"\nline","list":[true,false,null,-2.5e1],"empty":{}}"#;
+        let json = Json::parse(text).unwrap();
+        assert_eq!(json.get("id").and_then(Json::as_f64), Some(1.0));
+        assert_eq!(
+            json.get("name").and_then(Json::as_str),
+            Some("a \"quoted\"\nline")
+        );
+        assert_eq!(json.get("list").and_then(Json::as_array).unwrap().len(), 4);
+        assert_eq!(json.to_string(), text.replace("-2.5e1", "-25"));
+    }
+
+    #[test]
+    fn decodes_unicode_escapes() {
+        let json = Json::parse(r#"" \u00e9 \ud83d\ude00 ""#).unwrap();
+        assert_eq!(json.as_str(), Some(" é 😀 "));
+        assert_eq!(Json::from("\u{1}").to_string(), "\"\\u0001\"");
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for text in ["{", "[1,]", "{\"a\" 1}", "tru", "\"open", "1 2"] {
+            assert!(Json::parse(text).is_err(), "{}", text);
+        }
+    }
+}
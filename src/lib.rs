@@ -1,4 +1,44 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
+use std::rc::Rc;
+
+mod ast;
+mod bigint;
+mod builtins;
+mod conformance;
+mod coverage;
+mod debugger;
+mod diagnostics;
+mod evaluate;
+mod highlight;
+mod interpreter;
+mod json;
+mod lint;
+mod lsp;
+mod output;
+mod parser;
+mod profiler;
+mod runner;
+mod tokenizer;
+mod typecheck;
+
+pub use ast::{program_json, program_sexpr, AST_VERSION};
+pub use bigint::BigInt;
+pub use conformance::{run_tests, TestResult, TestSummary};
+pub use coverage::{BranchCounts, Coverage};
+pub use diagnostics::{set_error_format, ErrorFormat};
+pub use highlight::{highlight, HighlightFormat};
+pub use interpreter::{
+    lint, parse, parse_expression, typecheck, Error, HostFunction, Interpreter, Value,
+};
+pub use json::Json;
+pub use lint::{LintConfig, LintWarning};
+pub use lsp::serve as serve_lsp;
+pub use output::SharedBuffer;
+pub use profiler::{LineStats, Profile};
+pub use runner::Limits;
+pub use tokenizer::{tokenize, tokenize_lossless, tokens_json, LosslessToken, Trivia, TriviaKind};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 #[allow(non_camel_case_types)]
@@ -7,17 +47,40 @@ pub enum TokenType {
     RIGHT_PAREN,
     LEFT_BRACE,
     RIGHT_BRACE,
+    LEFT_BRACKET,
+    RIGHT_BRACKET,
 
     STAR,
+    STAR_STAR,
+    PERCENT,
+    PLUS_PERCENT,
+    MINUS_PERCENT,
+    STAR_PERCENT,
+    AMPERSAND,
+    PIPE,
+    CARET,
+    TILDE,
+    LESS_LESS,
+    GREATER_GREATER,
     DOT,
     COMMA,
     SEMICOLON,
+    COLON,
+    QUESTION,
+    FAT_ARROW,
     PLUS,
     MINUS,
     BANG,
 
     SLASH,
 
+    PLUS_EQUAL,
+    MINUS_EQUAL,
+    STAR_EQUAL,
+    SLASH_EQUAL,
+    PLUS_PLUS,
+    MINUS_MINUS,
+
     EQUAL,
     EQUAL_EQUAL,
     BANG_EQUAL,
@@ -28,9 +91,16 @@ pub enum TokenType {
 
     STRING,
     NUMBER,
+    /// Runtime only: number literals without a decimal point evaluate to integers.
+    INTEGER,
+    /// A literal such as `123n`; the literal holds the digits without the suffix.
+    BIGINT,
 
     IDENTIFIER,
 
+    // runtime values
+    MAP,
+
     EOF,
     INVALID,
 
@@ -51,6 +121,90 @@ pub enum TokenType {
     AND,
     OR,
     NIL,
+    IMPORT,
+    EXPORT,
+    DIV,
+    MATCH,
+}
+
+impl TokenType {
+    /// Whether this is one of the reserved words.
+    pub fn is_keyword(&self) -> bool {
+        matches!(
+            self,
+            TokenType::CLASS
+                | TokenType::SUPER
+                | TokenType::THIS
+                | TokenType::FUN
+                | TokenType::RETURN
+                | TokenType::VAR
+                | TokenType::TRUE
+                | TokenType::FALSE
+                | TokenType::PRINT
+                | TokenType::IF
+                | TokenType::ELSE
+                | TokenType::FOR
+                | TokenType::WHILE
+                | TokenType::AND
+                | TokenType::OR
+                | TokenType::NIL
+                | TokenType::IMPORT
+                | TokenType::EXPORT
+                | TokenType::DIV
+                | TokenType::MATCH
+        )
+    }
+
+    pub fn is_operator(&self) -> bool {
+        matches!(
+            self,
+            TokenType::STAR
+                | TokenType::STAR_STAR
+                | TokenType::PERCENT
+                | TokenType::PLUS_PERCENT
+                | TokenType::MINUS_PERCENT
+                | TokenType::STAR_PERCENT
+                | TokenType::AMPERSAND
+                | TokenType::PIPE
+                | TokenType::CARET
+                | TokenType::TILDE
+                | TokenType::LESS_LESS
+                | TokenType::GREATER_GREATER
+                | TokenType::PLUS
+                | TokenType::MINUS
+                | TokenType::BANG
+                | TokenType::SLASH
+                | TokenType::PLUS_EQUAL
+                | TokenType::MINUS_EQUAL
+                | TokenType::STAR_EQUAL
+                | TokenType::SLASH_EQUAL
+                | TokenType::PLUS_PLUS
+                | TokenType::MINUS_MINUS
+                | TokenType::EQUAL
+                | TokenType::EQUAL_EQUAL
+                | TokenType::BANG_EQUAL
+                | TokenType::LESS
+                | TokenType::LESS_EQUAL
+                | TokenType::GREATER
+                | TokenType::GREATER_EQUAL
+        )
+    }
+
+    /// Whether values of this type are numbers, either floats or integers.
+    pub fn is_number(&self) -> bool {
+        matches!(self, TokenType::NUMBER | TokenType::INTEGER)
+    }
+
+    /// The arithmetic a compound assignment (`+=`) or increment (`++`) applies to its target.
+    pub fn arithmetic_operator(&self) -> Option<TokenType> {
+        match self {
+            TokenType::PLUS_EQUAL | TokenType::PLUS_PLUS => Some(TokenType::PLUS),
+            TokenType::MINUS_EQUAL | TokenType::MINUS_MINUS => Some(TokenType::MINUS),
+            TokenType::STAR_EQUAL => Some(TokenType::STAR),
+            TokenType::SLASH_EQUAL => Some(TokenType::SLASH),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -59,6 +213,132 @@ pub struct Token {
     pub lexeme: String,
     pub literal: String,
     pub line_num: u32,
+    /// 1-based position of the token's first character on its line; 0 for synthesized tokens.
+    pub column: u32,
+    pub object: Option<Object>,
+}
+
+/// Heap values that can't be represented by a lexeme/literal pair. Cloning a token shares the
+/// underlying object, so these have reference semantics.
+#[derive(Debug, Clone)]
+pub enum Object {
+    Map(Rc<RefCell<LoxMap>>),
+}
+
+/// Insertion-ordered map used for `{key: value}` literals.
+#[derive(Debug, Default)]
+pub struct LoxMap {
+    entries: Vec<(Token, Token)>,
+    index: HashMap<String, usize>,
+}
+
+impl LoxMap {
+    pub fn new() -> Self {
+        LoxMap::default()
+    }
+
+    /// Returns the hash key for `key`, or `None` if values of its type can't be used as keys.
+    pub fn hash_key(key: &Token) -> Option<String> {
+        match key.token_type {
+            TokenType::STRING => Some(format!("s:{}", key.literal)),
+            // integers and floats with the same value are the same key, as they compare equal,
+            // so whole floats are hashed by their exact value like integers
+            TokenType::NUMBER => {
+                let val = key.literal.parse::<f64>().ok()?;
+                match BigInt::from_f64(val) {
+                    Some(whole) => Some(format!("n:{}", whole)),
+                    None => Some(format!("n:{}", val as f32)),
+                }
+            }
+            TokenType::INTEGER | TokenType::BIGINT => Some(format!("n:{}", key.literal)),
+            TokenType::TRUE | TokenType::FALSE | TokenType::NIL => {
+                Some(format!("{:?}", key.token_type))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, key: &Token) -> Option<Token> {
+        let idx = self.index.get(&LoxMap::hash_key(key)?)?;
+        Some(self.entries[*idx].1.clone())
+    }
+
+    pub fn contains(&self, key: &Token) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Inserts or updates `key`, returning false if the key can't be hashed. Existing keys keep
+    /// their original position.
+    pub fn insert(&mut self, key: Token, value: Token) -> bool {
+        let Some(hash_key) = LoxMap::hash_key(&key) else {
+            return false;
+        };
+        match self.index.get(&hash_key) {
+            Some(idx) => self.entries[*idx].1 = value,
+            None => {
+                self.index.insert(hash_key, self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+
+        true
+    }
+
+    pub fn remove(&mut self, key: &Token) -> Option<Token> {
+        let idx = self.index.remove(&LoxMap::hash_key(key)?)?;
+        let (_, value) = self.entries.remove(idx);
+        for i in self.index.values_mut() {
+            if *i > idx {
+                *i -= 1;
+            }
+        }
+
+        Some(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Keys in insertion order.
+    pub fn keys(&self) -> Vec<Token> {
+        self.entries.iter().map(|(key, _)| key.clone()).collect()
+    }
+}
+
+impl LoxMap {
+    /// Writes the map, with `{...}` for any map already being written further up `path`, so a
+    /// map that contains itself still prints.
+    fn write_nested(&self, f: &mut fmt::Formatter, path: &mut Vec<*const LoxMap>) -> fmt::Result {
+        if path.contains(&(self as *const LoxMap)) {
+            return write!(f, "{{...}}");
+        }
+
+        path.push(self);
+        write!(f, "{{")?;
+        for (i, (key, value)) in self.entries.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: ", key.lexeme)?;
+            match value.object {
+                Some(Object::Map(ref map)) => map.borrow().write_nested(f, path)?,
+                None => write!(f, "{}", value.lexeme)?,
+            }
+        }
+        path.pop();
+        write!(f, "}}")
+    }
+}
+
+impl fmt::Display for LoxMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_nested(f, &mut vec![])
+    }
 }
 
 impl fmt::Display for Token {
@@ -74,6 +354,27 @@ impl Token {
             lexeme,
             literal,
             line_num,
+            column: 0,
+            object: None,
+        }
+    }
+
+    pub fn new_map(map: LoxMap, line_num: u32) -> Self {
+        let mut token = Token::new(
+            TokenType::MAP,
+            String::from("<map>"),
+            String::from("null"),
+            line_num,
+        );
+        token.object = Some(Object::Map(Rc::new(RefCell::new(map))));
+
+        token
+    }
+
+    pub fn as_map(&self) -> Option<Rc<RefCell<LoxMap>>> {
+        match self.object {
+            Some(Object::Map(ref map)) => Some(Rc::clone(map)),
+            None => None,
         }
     }
 
@@ -100,8 +401,49 @@ impl Token {
             '}' => {
                 token.token_type = TokenType::RIGHT_BRACE;
             }
-            '*' => {
-                token.token_type = TokenType::STAR;
+            '[' => {
+                token.token_type = TokenType::LEFT_BRACKET;
+            }
+            ']' => {
+                token.token_type = TokenType::RIGHT_BRACKET;
+            }
+            '*' => match prev_lexeme {
+                '*' => {
+                    token.token_type = TokenType::STAR_STAR;
+                    token.lexeme = String::from("**");
+                }
+                _ => {
+                    token.token_type = TokenType::STAR;
+                }
+            },
+            '%' => match prev_lexeme {
+                '+' => {
+                    token.token_type = TokenType::PLUS_PERCENT;
+                    token.lexeme = String::from("+%");
+                }
+                '-' => {
+                    token.token_type = TokenType::MINUS_PERCENT;
+                    token.lexeme = String::from("-%");
+                }
+                '*' => {
+                    token.token_type = TokenType::STAR_PERCENT;
+                    token.lexeme = String::from("*%");
+                }
+                _ => {
+                    token.token_type = TokenType::PERCENT;
+                }
+            },
+            '&' => {
+                token.token_type = TokenType::AMPERSAND;
+            }
+            '|' => {
+                token.token_type = TokenType::PIPE;
+            }
+            '^' => {
+                token.token_type = TokenType::CARET;
+            }
+            '~' => {
+                token.token_type = TokenType::TILDE;
             }
             '.' => {
                 token.token_type = TokenType::DOT;
@@ -109,24 +451,58 @@ impl Token {
             ',' => {
                 token.token_type = TokenType::COMMA;
             }
-            '+' => {
-                token.token_type = TokenType::PLUS;
-            }
-            '-' => {
-                token.token_type = TokenType::MINUS;
-            }
+            '+' => match prev_lexeme {
+                '+' => {
+                    token.token_type = TokenType::PLUS_PLUS;
+                    token.lexeme = String::from("++");
+                }
+                _ => {
+                    token.token_type = TokenType::PLUS;
+                }
+            },
+            '-' => match prev_lexeme {
+                '-' => {
+                    token.token_type = TokenType::MINUS_MINUS;
+                    token.lexeme = String::from("--");
+                }
+                _ => {
+                    token.token_type = TokenType::MINUS;
+                }
+            },
             ';' => {
                 token.token_type = TokenType::SEMICOLON;
             }
-            '!' => {
-                token.token_type = TokenType::BANG;
+            ':' => {
+                token.token_type = TokenType::COLON;
             }
-            '<' => {
-                token.token_type = TokenType::LESS;
+            '?' => {
+                token.token_type = TokenType::QUESTION;
             }
-            '>' => {
-                token.token_type = TokenType::GREATER;
+            '!' => {
+                token.token_type = TokenType::BANG;
             }
+            '<' => match prev_lexeme {
+                '<' => {
+                    token.token_type = TokenType::LESS_LESS;
+                    token.lexeme = String::from("<<");
+                }
+                _ => {
+                    token.token_type = TokenType::LESS;
+                }
+            },
+            '>' => match prev_lexeme {
+                '>' => {
+                    token.token_type = TokenType::GREATER_GREATER;
+                    token.lexeme = String::from(">>");
+                }
+                '=' => {
+                    token.token_type = TokenType::FAT_ARROW;
+                    token.lexeme = String::from("=>");
+                }
+                _ => {
+                    token.token_type = TokenType::GREATER;
+                }
+            },
             '=' => match prev_lexeme {
                 '!' => {
                     token.token_type = TokenType::BANG_EQUAL;
@@ -144,6 +520,22 @@ impl Token {
                     token.token_type = TokenType::LESS_EQUAL;
                     token.lexeme = String::from("<=");
                 }
+                '+' => {
+                    token.token_type = TokenType::PLUS_EQUAL;
+                    token.lexeme = String::from("+=");
+                }
+                '-' => {
+                    token.token_type = TokenType::MINUS_EQUAL;
+                    token.lexeme = String::from("-=");
+                }
+                '*' => {
+                    token.token_type = TokenType::STAR_EQUAL;
+                    token.lexeme = String::from("*=");
+                }
+                '/' => {
+                    token.token_type = TokenType::SLASH_EQUAL;
+                    token.lexeme = String::from("/=");
+                }
                 _ => {
                     token.token_type = TokenType::EQUAL;
                 }
@@ -176,14 +568,27 @@ impl Token {
             "for" => self.token_type = TokenType::FOR,
             "while" => self.token_type = TokenType::WHILE,
             "nil" => self.token_type = TokenType::NIL,
+            "import" => self.token_type = TokenType::IMPORT,
+            "export" => self.token_type = TokenType::EXPORT,
+            "div" => self.token_type = TokenType::DIV,
+            "match" => self.token_type = TokenType::MATCH,
             _ => {}
         };
     }
 
     pub fn print(&self) {
+        println!("{}", self.value_string());
+    }
+
+    /// The text `print` shows for this value.
+    pub fn value_string(&self) -> String {
         match self.token_type {
-            TokenType::STRING => println!("{}", self.literal),
-            _ => println!("{}", self.lexeme),
+            TokenType::STRING => self.literal.clone(),
+            TokenType::MAP => match self.object {
+                Some(Object::Map(ref map)) => map.borrow().to_string(),
+                None => self.lexeme.clone(),
+            },
+            _ => self.lexeme.clone(),
         }
     }
 }
@@ -195,23 +600,124 @@ pub enum Expr {
     Literal(Token),
     Unary(Token, Box<Expr>),
     Logical(Box<Expr>, Token, Box<Expr>),
+    /// `condition ? then : otherwise`, keeping the `?` token.
+    Ternary(Box<Expr>, Token, Box<Expr>, Box<Expr>),
     Stmt(Statement),
-    Scope(Vec<Expr>),
+    Scope(Token, Vec<Expr>),
+    Map(Token, Vec<(Expr, Expr)>),
+    Index(Box<Expr>, Token, Box<Expr>),
+    Call(Token, Vec<Expr>),
 }
 
 #[derive(Clone)]
 pub enum Statement {
     PrintStmt(Box<Expr>),
-    DeclarationStmt(Token, Option<Box<Expr>>),
+    /// Variable, optional type annotation and initializer.
+    DeclarationStmt(Token, Option<Token>, Option<Box<Expr>>),
     AssignmentStmt(Token, Box<Expr>),
+    IndexAssignmentStmt(Box<Expr>, Token, Box<Expr>, Box<Expr>),
+    /// Variable or index target, operator (`+=`, `-=`, `*=` or `/=`) and value.
+    CompoundAssignmentStmt(Box<Expr>, Token, Box<Expr>),
+    /// Variable or index target, `++` or `--`, and whether the operator comes after the target.
+    IncrementStmt(Box<Expr>, Token, bool),
     IfStmt(Vec<Conditional>),
     ForStmt(Option<Box<Expr>>, Box<Expr>, Option<Box<Expr>>, Box<Expr>),
     WhileStmt(Conditional),
+    ImportStmt(Token, Option<Vec<Token>>),
+    ExportStmt(Box<Expr>),
+    /// The `match` keyword, the value matched on and the arms, tried in order.
+    MatchStmt(Token, Box<Expr>, Vec<MatchArm>),
 }
 
 #[derive(Clone)]
 pub struct Conditional(pub Box<Expr>, pub Box<Expr>);
 
+/// `pattern | pattern if guard => body` in a `match` statement.
+#[derive(Clone)]
+pub struct MatchArm {
+    /// Alternatives; the arm applies if any of them matches.
+    pub patterns: Vec<Pattern>,
+    pub guard: Option<Box<Expr>>,
+    pub body: Box<Expr>,
+}
+
+#[derive(Clone)]
+pub enum Pattern {
+    /// A literal, possibly negated, that the value must equal.
+    Value(Box<Expr>),
+    /// `_`, which matches anything.
+    Wildcard(Token),
+    /// A name that matches anything and holds the value in the arm's guard and body.
+    Binding(Token),
+}
+
+impl MatchArm {
+    /// Whether the arm matches every value, so arms after it can never run.
+    pub fn is_catch_all(&self) -> bool {
+        self.guard.is_none()
+            && self
+                .patterns
+                .iter()
+                .any(|pattern| !matches!(pattern, Pattern::Value(_)))
+    }
+}
+
+impl Expr {
+    /// Line of the first token in the expression, used to attribute statements to source lines.
+    pub fn line(&self) -> u32 {
+        self.first_token().line_num
+    }
+
+    /// Line and column of the first token in the expression, which tell apart expressions that
+    /// share a line.
+    pub fn position(&self) -> (u32, u32) {
+        let token = self.first_token();
+        (token.line_num, token.column)
+    }
+
+    fn first_token(&self) -> &Token {
+        match self {
+            Expr::Binary(left, ..)
+            | Expr::Logical(left, ..)
+            | Expr::Index(left, ..)
+            | Expr::Ternary(left, ..) => left.first_token(),
+            Expr::Grouping(expr) => expr.first_token(),
+            Expr::Literal(token)
+            | Expr::Unary(token, _)
+            | Expr::Map(token, _)
+            | Expr::Call(token, _)
+            | Expr::Scope(token, _) => token,
+            Expr::Stmt(statement) => statement.first_token(),
+        }
+    }
+}
+
+impl Statement {
+    pub fn line(&self) -> u32 {
+        self.first_token().line_num
+    }
+
+    fn first_token(&self) -> &Token {
+        match self {
+            Statement::PrintStmt(expr) | Statement::ExportStmt(expr) => expr.first_token(),
+            Statement::DeclarationStmt(variable, ..) | Statement::AssignmentStmt(variable, _) => {
+                variable
+            }
+            Statement::IndexAssignmentStmt(target, ..)
+            | Statement::CompoundAssignmentStmt(target, ..)
+            | Statement::IncrementStmt(target, ..) => target.first_token(),
+            Statement::IfStmt(conditionals) => conditionals[0].0.first_token(),
+            Statement::ForStmt(init, condition, ..) => match init {
+                Some(init) => init.first_token(),
+                None => condition.first_token(),
+            },
+            Statement::WhileStmt(conditional) => conditional.0.first_token(),
+            Statement::ImportStmt(path, _) => path,
+            Statement::MatchStmt(keyword, ..) => keyword,
+        }
+    }
+}
+
 impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -226,19 +732,40 @@ impl fmt::Display for Expr {
                 write!(f, "({} {} {})", operator.lexeme, left_val, right_val)
             }
             Expr::Logical(left_val, operator, right_val) => {
-                write!(f, "{} {} {}", left_val, operator.lexeme, right_val)
+                write!(f, "({} {} {})", operator.lexeme, left_val, right_val)
+            }
+            Expr::Ternary(condition, _, then, otherwise) => {
+                write!(f, "(?: {} {} {})", condition, then, otherwise)
             }
             Expr::Grouping(expression) => {
                 write!(f, "(group {})", expression)
             }
             Expr::Stmt(statement) => write!(f, "{}", statement),
-            Expr::Scope(exprs) => {
+            Expr::Scope(_, exprs) => {
                 write!(f, "scoped \n{{\n")?;
                 for expr in exprs {
                     write!(f, "{}\n", expr)?;
                 }
                 write!(f, "}}")
             }
+            Expr::Map(_, entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", key, value)?;
+                }
+                write!(f, "}}")
+            }
+            Expr::Index(target, _, key) => write!(f, "(index {} {})", target, key),
+            Expr::Call(callee, args) => {
+                write!(f, "(call {}", callee.lexeme)?;
+                for arg in args {
+                    write!(f, " {}", arg)?;
+                }
+                write!(f, ")")
+            }
         }
     }
 }
@@ -247,13 +774,31 @@ impl fmt::Display for Statement {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Statement::PrintStmt(expr) => write!(f, "print {}", expr),
-            Statement::DeclarationStmt(variable, value) => match value {
-                Some(val_expr) => write!(f, "declare {} = {}", variable.lexeme, val_expr),
-                None => write!(f, "declare {} = nil", variable.lexeme),
-            },
+            Statement::DeclarationStmt(variable, annotation, value) => {
+                write!(f, "declare {}", variable.lexeme)?;
+                if let Some(annotation) = annotation {
+                    write!(f, ": {}", annotation.lexeme)?;
+                }
+                match value {
+                    Some(val_expr) => write!(f, " = {}", val_expr),
+                    None => write!(f, " = nil"),
+                }
+            }
             Statement::AssignmentStmt(variable, value) => {
                 write!(f, "assign {} with {}", variable.lexeme, value)
             }
+            Statement::IndexAssignmentStmt(target, _, key, value) => {
+                write!(f, "assign {}[{}] with {}", target, key, value)
+            }
+            Statement::CompoundAssignmentStmt(target, operator, value) => {
+                write!(f, "assign {} {} {}", target, operator.lexeme, value)
+            }
+            Statement::IncrementStmt(target, operator, true) => {
+                write!(f, "({} {})", target, operator.lexeme)
+            }
+            Statement::IncrementStmt(target, operator, false) => {
+                write!(f, "({} {})", operator.lexeme, target)
+            }
             Statement::IfStmt(conditionals) => {
                 write!(f, "if {}", conditionals[0])?;
                 let blocks = conditionals.len();
@@ -275,6 +820,22 @@ impl fmt::Display for Statement {
                 write!(f, "\nend if")
             }
             Statement::WhileStmt(conditional) => write!(f, "while {}\nend while", conditional),
+            Statement::ImportStmt(path, names) => match names {
+                Some(names) => {
+                    let names: Vec<&str> = names.iter().map(|name| name.lexeme.as_str()).collect();
+                    write!(f, "import {{ {} }} from {}", names.join(", "), path.lexeme)
+                }
+                None => write!(f, "import {}", path.lexeme),
+            },
+            Statement::ExportStmt(declaration) => write!(f, "export {}", declaration),
+            Statement::MatchStmt(_, value, arms) => {
+                write!(f, "match {}", value)?;
+                for arm in arms {
+                    write!(f, "\n{}", arm)?;
+                }
+
+                write!(f, "\nend match")
+            }
             Statement::ForStmt(var_init, condition, var_update, expr) => {
                 write!(f, "for ( ")?;
                 match var_init {
@@ -299,3 +860,25 @@ impl fmt::Display for Conditional {
         write!(f, "condition: {}\n{}", self.0, self.1)
     }
 }
+
+impl fmt::Display for MatchArm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let patterns: Vec<String> = self.patterns.iter().map(Pattern::to_string).collect();
+        write!(f, "case {}", patterns.join(" | "))?;
+        if let Some(guard) = &self.guard {
+            write!(f, " if {}", guard)?;
+        }
+
+        write!(f, "\n{}", self.body)
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Pattern::Value(value) => write!(f, "{}", value),
+            Pattern::Wildcard(_) => write!(f, "_"),
+            Pattern::Binding(name) => write!(f, "bind {}", name.lexeme),
+        }
+    }
+}
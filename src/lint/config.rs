@@ -0,0 +1,100 @@
+use super::RULES;
+use std::collections::{HashMap, HashSet};
+
+/// Which lint rules are enabled. Every rule is on unless the config file turns it off.
+#[derive(Debug, Default, Clone)]
+pub struct LintConfig {
+    disabled: HashSet<String>,
+}
+
+impl LintConfig {
+    /// Reads a config file of `rule = on|off` lines. `#` starts a comment.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut config = LintConfig::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let Some((rule, setting)) = line.split_once('=') else {
+                return Err(format!("Expected 'rule = on|off' on line {}", index + 1));
+            };
+            let rule = rule.trim();
+            if !RULES.contains(&rule) {
+                return Err(format!(
+                    "Unknown lint rule '{}' on line {}",
+                    rule,
+                    index + 1
+                ));
+            }
+
+            match setting.trim() {
+                "on" => {
+                    config.disabled.remove(rule);
+                }
+                "off" => {
+                    config.disabled.insert(rule.to_string());
+                }
+                other => {
+                    return Err(format!(
+                        "Expected 'on' or 'off' for '{}' but found '{}' on line {}",
+                        rule,
+                        other,
+                        index + 1
+                    ))
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub fn is_enabled(&self, rule: &str) -> bool {
+        !self.disabled.contains(rule)
+    }
+}
+
+/// Lines silenced by `// lint-ignore` comments, with the rules they name; an empty list silences
+/// every rule. A comment after code applies to its own line, one on a line of its own to the next.
+pub fn ignored_lines(source: &str) -> HashMap<u32, Vec<String>> {
+    let mut ignored = HashMap::new();
+    for (index, line) in source.lines().enumerate() {
+        let Some(start) = comment_start(line) else {
+            continue;
+        };
+        let Some(rules) = line[start + 2..].trim().strip_prefix("lint-ignore") else {
+            continue;
+        };
+
+        let rules = rules
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|rule| !rule.is_empty())
+            .map(String::from)
+            .collect();
+        let line_num = index as u32 + 1;
+        let target = match line[..start].trim().is_empty() {
+            true => line_num + 1,
+            false => line_num,
+        };
+        ignored.insert(target, rules);
+    }
+
+    ignored
+}
+
+/// Byte offset of the `//` starting a comment, skipping any inside string literals.
+fn comment_start(line: &str) -> Option<usize> {
+    let mut in_string = false;
+    let mut prev = ' ';
+    for (offset, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '/' if prev == '/' && !in_string => return Some(offset - 1),
+            _ => {}
+        }
+        prev = c;
+    }
+
+    None
+}
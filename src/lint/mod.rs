@@ -0,0 +1,342 @@
+use crate::{Expr, Pattern, Statement, Token, TokenType};
+use std::collections::HashSet;
+use std::fmt;
+mod config;
+mod tests;
+
+pub use config::LintConfig;
+
+pub const UNUSED_VARIABLE: &str = "unused-variable";
+pub const SHADOWING: &str = "shadowing";
+pub const ASSIGNMENT_CONDITION: &str = "assignment-condition";
+pub const UNREACHABLE_CODE: &str = "unreachable-code";
+pub const EMPTY_BLOCK: &str = "empty-block";
+pub const MIXED_COMPARISON: &str = "mixed-comparison";
+
+/// Every rule name a config file or `// lint-ignore` comment can refer to.
+pub const RULES: [&str; 6] = [
+    UNUSED_VARIABLE,
+    SHADOWING,
+    ASSIGNMENT_CONDITION,
+    UNREACHABLE_CODE,
+    EMPTY_BLOCK,
+    MIXED_COMPARISON,
+];
+
+#[derive(Debug, PartialEq)]
+pub struct LintWarning {
+    pub rule: &'static str,
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[line {}, column {}] Warning: {}. ({})",
+            self.line, self.column, self.message, self.rule
+        )
+    }
+}
+
+/// Walks a parsed program and returns the warnings of every enabled rule that aren't silenced
+/// by a `// lint-ignore` comment in `source`, in source order.
+pub fn lint(program: &[Expr], source: &str, config: &LintConfig) -> Vec<LintWarning> {
+    let mut linter = Linter {
+        scopes: vec![vec![]],
+        exported: HashSet::new(),
+        warnings: vec![],
+    };
+    linter.statements(program);
+    linter.end_scope();
+
+    let ignored = config::ignored_lines(source);
+    let mut warnings: Vec<LintWarning> = linter
+        .warnings
+        .into_iter()
+        .filter(|warning| config.is_enabled(warning.rule))
+        .filter(|warning| match ignored.get(&warning.line) {
+            Some(rules) => !rules.is_empty() && !rules.iter().any(|rule| rule == warning.rule),
+            None => true,
+        })
+        .collect();
+    warnings.sort_by_key(|warning| (warning.line, warning.column));
+
+    warnings
+}
+
+struct Variable {
+    token: Token,
+    used: bool,
+}
+
+struct Linter {
+    scopes: Vec<Vec<Variable>>,
+    exported: HashSet<String>,
+    warnings: Vec<LintWarning>,
+}
+
+impl Linter {
+    fn statements(&mut self, exprs: &[Expr]) {
+        for expr in exprs {
+            self.statement(expr);
+        }
+    }
+
+    fn statement(&mut self, expr: &Expr) {
+        let statement = match expr {
+            Expr::Scope(brace, exprs) => {
+                if exprs.is_empty() {
+                    self.warn(EMPTY_BLOCK, brace, String::from("Empty block"));
+                }
+
+                self.scopes.push(vec![]);
+                self.statements(exprs);
+                self.end_scope();
+                return;
+            }
+            Expr::Stmt(statement) => statement,
+            _ => return self.expression(expr),
+        };
+
+        match statement {
+            Statement::DeclarationStmt(variable, _, value) => {
+                if let Some(value) = value {
+                    self.expression(value);
+                }
+                self.declare(variable);
+            }
+            Statement::IfStmt(conditionals) => {
+                for conditional in conditionals {
+                    // the parser gives `else` a literal `true` rather than a condition
+                    if let Expr::Grouping(_) = *conditional.0 {
+                        self.condition(&conditional.0);
+                    }
+                    self.statement(&conditional.1);
+                }
+            }
+            Statement::WhileStmt(conditional) => {
+                self.condition(&conditional.0);
+                self.statement(&conditional.1);
+            }
+            Statement::MatchStmt(_, value, arms) => {
+                self.expression(value);
+                for arm in arms {
+                    self.scopes.push(vec![]);
+                    for pattern in arm.patterns.iter() {
+                        if let Pattern::Binding(name) = pattern {
+                            self.declare(name);
+                        }
+                    }
+                    if let Some(guard) = &arm.guard {
+                        self.condition(guard);
+                    }
+                    self.statement(&arm.body);
+                    self.end_scope();
+                }
+            }
+            Statement::ForStmt(init, condition, update, body) => {
+                self.scopes.push(vec![]);
+                if let Some(init) = init {
+                    self.statement(init);
+                }
+                self.condition(condition);
+                if let Some(update) = update {
+                    self.statement(update);
+                }
+                self.statement(body);
+                self.end_scope();
+            }
+            Statement::ImportStmt(_, names) => {
+                for name in names.iter().flatten() {
+                    self.declare(name);
+                }
+            }
+            Statement::ExportStmt(declaration) => {
+                if let Expr::Stmt(Statement::DeclarationStmt(ref variable, ..)) = **declaration {
+                    self.exported.insert(variable.lexeme.clone());
+                }
+                self.statement(declaration);
+            }
+            Statement::PrintStmt(_)
+            | Statement::AssignmentStmt(..)
+            | Statement::IndexAssignmentStmt(..)
+            | Statement::CompoundAssignmentStmt(..)
+            | Statement::IncrementStmt(..) => self.expression(expr),
+        }
+    }
+
+    fn condition(&mut self, condition: &Expr) {
+        match ungroup(condition) {
+            Expr::Stmt(Statement::AssignmentStmt(variable, _)) => self.warn(
+                ASSIGNMENT_CONDITION,
+                variable,
+                String::from("Assignment used as a condition, did you mean '=='"),
+            ),
+            Expr::Literal(
+                token @ Token {
+                    token_type: TokenType::FALSE | TokenType::NIL,
+                    ..
+                },
+            ) => self.warn(
+                UNREACHABLE_CODE,
+                token,
+                format!(
+                    "Unreachable code: the condition is always '{}'",
+                    token.lexeme
+                ),
+            ),
+            _ => {}
+        }
+
+        self.expression(condition);
+    }
+
+    fn expression(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(token) => {
+                if token.token_type == TokenType::IDENTIFIER {
+                    self.use_var(&token.lexeme);
+                }
+            }
+            Expr::Binary(left, operator, right) => {
+                self.comparison(left, operator, right);
+                self.expression(left);
+                self.expression(right);
+            }
+            Expr::Logical(left, _, right) | Expr::Index(left, _, right) => {
+                self.expression(left);
+                self.expression(right);
+            }
+            Expr::Ternary(condition, _, then, otherwise) => {
+                self.expression(condition);
+                self.expression(then);
+                self.expression(otherwise);
+            }
+            Expr::Grouping(expr) | Expr::Unary(_, expr) => self.expression(expr),
+            Expr::Stmt(Statement::PrintStmt(value))
+            | Expr::Stmt(Statement::AssignmentStmt(_, value)) => self.expression(value),
+            Expr::Stmt(Statement::IndexAssignmentStmt(target, _, key, value)) => {
+                self.expression(target);
+                self.expression(key);
+                self.expression(value);
+            }
+            Expr::Stmt(Statement::CompoundAssignmentStmt(target, _, value)) => {
+                self.expression(target);
+                self.expression(value);
+            }
+            Expr::Stmt(Statement::IncrementStmt(target, ..)) => self.expression(target),
+            Expr::Stmt(_) | Expr::Scope(..) => self.statement(expr),
+            Expr::Map(_, entries) => {
+                for (key, value) in entries {
+                    self.expression(key);
+                    self.expression(value);
+                }
+            }
+            Expr::Call(_, args) => {
+                for arg in args {
+                    self.expression(arg);
+                }
+            }
+        }
+    }
+
+    /// Flags comparisons between literals of different types, which are never equal.
+    fn comparison(&mut self, left: &Expr, operator: &Token, right: &Expr) {
+        if !matches!(
+            operator.token_type,
+            TokenType::EQUAL_EQUAL
+                | TokenType::BANG_EQUAL
+                | TokenType::GREATER
+                | TokenType::GREATER_EQUAL
+                | TokenType::LESS
+                | TokenType::LESS_EQUAL
+        ) {
+            return;
+        }
+
+        if let (Some(left), Some(right)) = (literal_type(left), literal_type(right)) {
+            if left != right {
+                self.warn(
+                    MIXED_COMPARISON,
+                    operator,
+                    format!("Comparison between a {} and a {}", left, right),
+                );
+            }
+        }
+    }
+
+    fn declare(&mut self, variable: &Token) {
+        let (current, outer) = self.scopes.split_last_mut().unwrap();
+        let shadows = outer
+            .iter()
+            .any(|scope| scope.iter().any(|var| var.token.lexeme == variable.lexeme));
+        current.push(Variable {
+            token: variable.clone(),
+            used: false,
+        });
+
+        if shadows {
+            self.warn(
+                SHADOWING,
+                variable,
+                format!("'{}' shadows a variable in an outer scope", variable.lexeme),
+            );
+        }
+    }
+
+    fn use_var(&mut self, name: &str) {
+        let found = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.iter_mut().rev().find(|var| var.token.lexeme == name));
+        if let Some(var) = found {
+            var.used = true;
+        }
+    }
+
+    fn end_scope(&mut self) {
+        let scope = self.scopes.pop().unwrap_or_default();
+        for var in scope {
+            if !var.used && !self.exported.contains(&var.token.lexeme) {
+                self.warn(
+                    UNUSED_VARIABLE,
+                    &var.token,
+                    format!("Variable '{}' is never used", var.token.lexeme),
+                );
+            }
+        }
+    }
+
+    fn warn(&mut self, rule: &'static str, token: &Token, message: String) {
+        self.warnings.push(LintWarning {
+            rule,
+            line: token.line_num,
+            column: token.column,
+            message,
+        });
+    }
+}
+
+fn ungroup(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Grouping(inner) => ungroup(inner),
+        _ => expr,
+    }
+}
+
+fn literal_type(expr: &Expr) -> Option<&'static str> {
+    match ungroup(expr) {
+        Expr::Literal(token) => match token.token_type {
+            TokenType::NUMBER | TokenType::BIGINT => Some("number"),
+            TokenType::STRING => Some("string"),
+            TokenType::TRUE | TokenType::FALSE => Some("boolean"),
+            TokenType::NIL => Some("nil"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
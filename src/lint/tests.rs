@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use crate::lint::{lint, LintConfig, RULES};
+    use crate::parse;
+
+    fn warnings(source: &str, config: &LintConfig) -> Vec<String> {
+        let program = parse(source).unwrap();
+        lint(&program, source, config)
+            .iter()
+            .map(|warning| warning.to_string())
+            .collect()
+    }
+
+    const SOURCE: &str = "var unused = 1;
+var x = 0;
+{
+  var x = 2;
+  print x;
+}
+if (x = 3) print x;
+while (false) {}
+print 1 == \"1\";";
+
+    #[test]
+    fn reports_every_rule() {
+        assert_eq!(
+            warnings(SOURCE, &LintConfig::default()),
+            [
+                "[line 1, column 5] Warning: Variable 'unused' is never used. (unused-variable)",
+                "[line 4, column 7] Warning: 'x' shadows a variable in an outer scope. (shadowing)",
+                "[line 7, column 5] Warning: Assignment used as a condition, did you mean '=='. (assignment-condition)",
+                "[line 8, column 8] Warning: Unreachable code: the condition is always 'false'. (unreachable-code)",
+                "[line 8, column 15] Warning: Empty block. (empty-block)",
+                "[line 9, column 9] Warning: Comparison between a number and a string. (mixed-comparison)",
+            ]
+        );
+    }
+
+    #[test]
+    fn config_disables_rules() {
+        let config =
+            LintConfig::parse("# quieter\nshadowing = off\nempty-block=off\nunused-variable = on")
+                .unwrap();
+        let found = warnings(SOURCE, &config);
+        assert_eq!(found.len(), 4);
+        assert!(found
+            .iter()
+            .all(|w| !w.contains("shadowing") && !w.contains("empty-block")));
+
+        assert!(LintConfig::parse("no-such-rule = off").is_err());
+        assert!(LintConfig::parse("shadowing = maybe").is_err());
+        assert_eq!(RULES.len(), 6);
+    }
+
+    #[test]
+    fn lint_ignore_comments() {
+        let source = "var a = 1; // lint-ignore\n// lint-ignore shadowing\nvar b = \"//\"; // lint-ignore empty-block\n{}";
+        assert_eq!(
+            warnings(source, &LintConfig::default()),
+            [
+                "[line 3, column 5] Warning: Variable 'b' is never used. (unused-variable)",
+                "[line 4, column 1] Warning: Empty block. (empty-block)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,405 @@
+use crate::diagnostics::report;
+use crate::output::{SharedBuffer, Sinks};
+use crate::parser::Parser;
+use crate::tokenizer::tokenize;
+use crate::typecheck::{self, Type};
+use crate::{Expr, Pattern, Statement, Token, TokenType};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Semantic token kinds, in the order advertised to the client.
+pub const TOKEN_TYPES: [&str; 6] = [
+    "keyword", "variable", "function", "string", "number", "operator",
+];
+
+/// A zero-based line and UTF-16 column, as LSP counts positions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub character: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+pub struct Diagnostic {
+    pub range: Range,
+    pub message: String,
+    /// Whether the program still runs, like a `match` without a wildcard arm.
+    pub warning: bool,
+}
+
+pub struct Declaration {
+    pub token: Token,
+    pub kind: Type,
+}
+
+/// Everything the server knows about one open file, rebuilt on every change.
+pub struct Document {
+    lines: Vec<String>,
+    tokens: Vec<Token>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub declarations: Vec<Declaration>,
+    /// Every use of a declared variable, with the index of its declaration.
+    references: Vec<(Token, usize)>,
+}
+
+impl Document {
+    pub fn new(text: &str) -> Self {
+        let buffer = SharedBuffer::new();
+        let sinks = Sinks {
+            output: None,
+            diagnostics: Some(Rc::new(RefCell::new(buffer.clone()))),
+        };
+        let guard = sinks.install();
+        let (tokens, status_code) = tokenize(text.to_string());
+        let mut parser = Parser::new(tokens.clone());
+        let program = match status_code {
+            0 => parser.parse().ok(),
+            _ => None,
+        };
+        for warning in parser.warnings() {
+            report(warning);
+        }
+        drop(guard);
+
+        let mut document = Document {
+            lines: text.lines().map(String::from).collect(),
+            tokens,
+            diagnostics: vec![],
+            declarations: vec![],
+            references: vec![],
+        };
+        for message in buffer.contents().lines() {
+            document.add_diagnostic(message);
+        }
+
+        if let Some(program) = program {
+            let mut resolver = Resolver {
+                scopes: vec![vec![]],
+                document: &mut document,
+            };
+            resolver.statements(&program);
+        }
+
+        document
+    }
+
+    /// Turns a `[line N] Error...` or `[line N] Warning...` message from the tokenizer or parser into a diagnostic,
+    /// pointing at the offending token when the message names one.
+    fn add_diagnostic(&mut self, message: &str) {
+        let Some((line, text)) = message
+            .strip_prefix("[line ")
+            .and_then(|rest| rest.split_once("] "))
+        else {
+            return;
+        };
+        let line = match line.parse::<usize>() {
+            // the end of file token has no line of its own
+            Ok(0) | Err(_) => self.lines.len().max(1),
+            Ok(line) => line,
+        };
+
+        let warning = text.starts_with("Warning");
+        let lexeme = text
+            .strip_prefix("Error at '")
+            .or_else(|| text.strip_prefix("Warning at '"))
+            .and_then(|rest| rest.split_once("':"))
+            .map(|(lexeme, _)| lexeme);
+        let token = self
+            .tokens
+            .iter()
+            .find(|token| token.line_num as usize == line && Some(token.lexeme.as_str()) == lexeme);
+        let range = match token {
+            Some(token) => self.token_range(token),
+            None => {
+                let end = self.lines.get(line - 1).map_or(0, |text| utf16_len(text));
+                Range {
+                    start: Position {
+                        line: line - 1,
+                        character: 0,
+                    },
+                    end: Position {
+                        line: line - 1,
+                        character: end,
+                    },
+                }
+            }
+        };
+
+        self.diagnostics.push(Diagnostic {
+            range,
+            message: text.to_string(),
+            warning,
+        });
+    }
+
+    pub fn token_range(&self, token: &Token) -> Range {
+        let line = (token.line_num as usize).saturating_sub(1);
+        let text = self.lines.get(line).map_or("", String::as_str);
+        let start = utf16_offset(text, (token.column as usize).saturating_sub(1));
+        Range {
+            start: Position {
+                line,
+                character: start,
+            },
+            end: Position {
+                line,
+                character: start + utf16_len(&token.lexeme),
+            },
+        }
+    }
+
+    /// The token under the cursor, if any.
+    pub fn token_at(&self, position: Position) -> Option<&Token> {
+        self.tokens.iter().find(|token| {
+            let range = self.token_range(token);
+            token.token_type != TokenType::EOF
+                && range.start.line == position.line
+                && range.start.character <= position.character
+                && position.character < range.end.character
+        })
+    }
+
+    /// The declaration a variable token refers to, or declares.
+    pub fn declaration_of(&self, token: &Token) -> Option<usize> {
+        let same = |other: &Token| other.line_num == token.line_num && other.column == token.column;
+        if let Some(index) = self.declarations.iter().position(|decl| same(&decl.token)) {
+            return Some(index);
+        }
+
+        self.references
+            .iter()
+            .find(|(reference, _)| same(reference))
+            .map(|(_, index)| *index)
+    }
+
+    pub fn references_to(&self, declaration: usize) -> Vec<&Token> {
+        self.references
+            .iter()
+            .filter(|(_, index)| *index == declaration)
+            .map(|(token, _)| token)
+            .collect()
+    }
+
+    /// Semantic tokens in the LSP relative encoding: line delta, start delta, length, type and
+    /// modifiers for each token.
+    pub fn semantic_tokens(&self) -> Vec<usize> {
+        let mut data = vec![];
+        let mut prev = Position {
+            line: 0,
+            character: 0,
+        };
+        for (i, token) in self.tokens.iter().enumerate() {
+            let next_type = self.tokens.get(i + 1).map(|next| next.token_type);
+            let Some(kind) = semantic_type(token.token_type, next_type) else {
+                continue;
+            };
+
+            let range = self.token_range(token);
+            let delta_start = match range.start.line == prev.line {
+                true => range.start.character - prev.character,
+                false => range.start.character,
+            };
+            data.extend([
+                range.start.line - prev.line,
+                delta_start,
+                range.end.character - range.start.character,
+                kind,
+                0,
+            ]);
+            prev = range.start;
+        }
+
+        data
+    }
+}
+
+fn semantic_type(token_type: TokenType, next: Option<TokenType>) -> Option<usize> {
+    let name = match token_type {
+        TokenType::IDENTIFIER if next == Some(TokenType::LEFT_PAREN) => "function",
+        TokenType::IDENTIFIER => "variable",
+        TokenType::STRING => "string",
+        TokenType::NUMBER | TokenType::BIGINT => "number",
+        t if t.is_keyword() => "keyword",
+        t if t.is_operator() => "operator",
+        _ => return None,
+    };
+
+    TOKEN_TYPES.iter().position(|kind| *kind == name)
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.chars().map(char::len_utf16).sum()
+}
+
+fn utf16_offset(text: &str, chars: usize) -> usize {
+    text.chars().take(chars).map(char::len_utf16).sum()
+}
+
+/// Matches variable uses to their declarations, following block scoping.
+struct Resolver<'a> {
+    scopes: Vec<Vec<usize>>,
+    document: &'a mut Document,
+}
+
+impl Resolver<'_> {
+    fn statements(&mut self, exprs: &[Expr]) {
+        for expr in exprs {
+            self.statement(expr);
+        }
+    }
+
+    fn statement(&mut self, expr: &Expr) {
+        let statement = match expr {
+            Expr::Scope(_, exprs) => {
+                self.scopes.push(vec![]);
+                self.statements(exprs);
+                self.scopes.pop();
+                return;
+            }
+            Expr::Stmt(statement) => statement,
+            _ => return self.expression(expr),
+        };
+
+        match statement {
+            Statement::DeclarationStmt(variable, annotation, value) => {
+                if let Some(value) = value {
+                    self.expression(value);
+                }
+
+                let kind = match (annotation, value) {
+                    (Some(annotation), _) => Type::from_name(&annotation.lexeme),
+                    (None, Some(value)) => Some(typecheck::infer(value)),
+                    (None, None) => Some(Type::Nil),
+                };
+                self.declare(variable, kind.unwrap_or(Type::Any));
+            }
+            Statement::IfStmt(conditionals) => {
+                for conditional in conditionals {
+                    self.expression(&conditional.0);
+                    self.statement(&conditional.1);
+                }
+            }
+            Statement::WhileStmt(conditional) => {
+                self.expression(&conditional.0);
+                self.statement(&conditional.1);
+            }
+            Statement::MatchStmt(_, value, arms) => {
+                self.expression(value);
+                let kind = typecheck::infer(value);
+                for arm in arms {
+                    self.scopes.push(vec![]);
+                    for pattern in arm.patterns.iter() {
+                        if let Pattern::Binding(name) = pattern {
+                            self.declare(name, kind);
+                        }
+                    }
+                    if let Some(guard) = &arm.guard {
+                        self.expression(guard);
+                    }
+                    self.statement(&arm.body);
+                    self.scopes.pop();
+                }
+            }
+            Statement::ForStmt(init, condition, update, body) => {
+                self.scopes.push(vec![]);
+                if let Some(init) = init {
+                    self.statement(init);
+                }
+                self.expression(condition);
+                if let Some(update) = update {
+                    self.statement(update);
+                }
+                self.statement(body);
+                self.scopes.pop();
+            }
+            Statement::ImportStmt(_, names) => {
+                for name in names.iter().flatten() {
+                    self.declare(name, Type::Any);
+                }
+            }
+            Statement::ExportStmt(declaration) => self.statement(declaration),
+            Statement::PrintStmt(_)
+            | Statement::AssignmentStmt(..)
+            | Statement::IndexAssignmentStmt(..)
+            | Statement::CompoundAssignmentStmt(..)
+            | Statement::IncrementStmt(..) => self.expression(expr),
+        }
+    }
+
+    fn expression(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(token) => {
+                if token.token_type == TokenType::IDENTIFIER {
+                    self.reference(token);
+                }
+            }
+            Expr::Binary(left, _, right)
+            | Expr::Logical(left, _, right)
+            | Expr::Index(left, _, right) => {
+                self.expression(left);
+                self.expression(right);
+            }
+            Expr::Ternary(condition, _, then, otherwise) => {
+                self.expression(condition);
+                self.expression(then);
+                self.expression(otherwise);
+            }
+            Expr::Grouping(expr) | Expr::Unary(_, expr) => self.expression(expr),
+            Expr::Stmt(Statement::PrintStmt(value)) => self.expression(value),
+            Expr::Stmt(Statement::AssignmentStmt(variable, value)) => {
+                self.expression(value);
+                self.reference(variable);
+            }
+            Expr::Stmt(Statement::IndexAssignmentStmt(target, _, key, value)) => {
+                self.expression(target);
+                self.expression(key);
+                self.expression(value);
+            }
+            Expr::Stmt(Statement::CompoundAssignmentStmt(target, _, value)) => {
+                self.expression(target);
+                self.expression(value);
+            }
+            Expr::Stmt(Statement::IncrementStmt(target, ..)) => self.expression(target),
+            Expr::Stmt(_) | Expr::Scope(..) => self.statement(expr),
+            Expr::Map(_, entries) => {
+                for (key, value) in entries {
+                    self.expression(key);
+                    self.expression(value);
+                }
+            }
+            Expr::Call(_, args) => {
+                for arg in args {
+                    self.expression(arg);
+                }
+            }
+        }
+    }
+
+    fn declare(&mut self, variable: &Token, kind: Type) {
+        self.document.declarations.push(Declaration {
+            token: variable.clone(),
+            kind,
+        });
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(self.document.declarations.len() - 1);
+        }
+    }
+
+    fn reference(&mut self, token: &Token) {
+        let declarations = &self.document.declarations;
+        let found = self.scopes.iter().rev().find_map(|scope| {
+            scope
+                .iter()
+                .rev()
+                .find(|index| declarations[**index].token.lexeme == token.lexeme)
+        });
+        if let Some(index) = found {
+            self.document.references.push((token.clone(), *index));
+        }
+    }
+}
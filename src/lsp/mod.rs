@@ -0,0 +1,398 @@
+use crate::json::Json;
+use crate::TokenType;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+mod analysis;
+mod tests;
+
+use analysis::{Document, Position, Range, TOKEN_TYPES};
+
+/// LSP `SymbolKind` for variables.
+const SYMBOL_KIND_VARIABLE: u32 = 13;
+/// JSON-RPC error codes.
+const PARSE_ERROR: f64 = -32700.0;
+const METHOD_NOT_FOUND: f64 = -32601.0;
+/// Largest message body accepted, so a bad `Content-Length` can't allocate without bound.
+const MAX_MESSAGE_LENGTH: usize = 64 * 1024 * 1024;
+
+/// Runs a language server speaking JSON-RPC over `input` and `output` until the client sends
+/// `exit` or closes the stream.
+pub fn serve<R: BufRead, W: Write>(mut input: R, mut output: W) -> io::Result<()> {
+    let mut server = Server {
+        documents: HashMap::new(),
+    };
+
+    while let Some(body) = read_message(&mut input)? {
+        let replies = match Json::parse(&body) {
+            Ok(message) => server.handle(&message),
+            Err(_) => vec![Reply::Send(error_response(
+                Json::Null,
+                PARSE_ERROR,
+                "Parse error",
+            ))],
+        };
+
+        for reply in replies {
+            match reply {
+                Reply::Exit => return Ok(()),
+                Reply::Send(message) => write_message(&mut output, &message)?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads one `Content-Length` framed message, or `None` at end of input.
+fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<String>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, val)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = val.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = length.ok_or_else(|| invalid_data("Missing Content-Length header"))?;
+    if length > MAX_MESSAGE_LENGTH {
+        return Err(invalid_data("Content-Length is too large"));
+    }
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|_| invalid_data("Message is not valid UTF-8"))
+}
+
+fn write_message<W: Write>(output: &mut W, message: &Json) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+enum Reply {
+    Send(Json),
+    Exit,
+}
+
+struct Server {
+    documents: HashMap<String, Document>,
+}
+
+impl Server {
+    fn handle(&mut self, message: &Json) -> Vec<Reply> {
+        let method = message.get("method").and_then(Json::as_str).unwrap_or("");
+        let params = message.get("params").cloned().unwrap_or(Json::Null);
+        let Some(id) = message.get("id").cloned() else {
+            return self.notification(method, &params);
+        };
+
+        let result = match method {
+            "initialize" => Some(capabilities()),
+            "shutdown" => Some(Json::Null),
+            "textDocument/hover" => Some(self.hover(&params)),
+            "textDocument/definition" => Some(self.definition(&params)),
+            "textDocument/references" => Some(self.references(&params)),
+            "textDocument/documentSymbol" => Some(self.symbols(&params)),
+            "textDocument/semanticTokens/full" => Some(self.semantic_tokens(&params)),
+            _ => None,
+        };
+
+        let response = match result {
+            Some(result) => Json::object(vec![
+                ("jsonrpc", Json::from("2.0")),
+                ("id", id),
+                ("result", result),
+            ]),
+            None => error_response(id, METHOD_NOT_FOUND, "Method not found"),
+        };
+        vec![Reply::Send(response)]
+    }
+
+    fn notification(&mut self, method: &str, params: &Json) -> Vec<Reply> {
+        let uri = params
+            .get("textDocument")
+            .and_then(|doc| doc.get("uri"))
+            .and_then(Json::as_str)
+            .unwrap_or("")
+            .to_string();
+
+        let text = match method {
+            "exit" => return vec![Reply::Exit],
+            "textDocument/didOpen" => params
+                .get("textDocument")
+                .and_then(|doc| doc.get("text"))
+                .and_then(Json::as_str),
+            // the server asks for full sync, so the last change holds the whole text
+            "textDocument/didChange" => params
+                .get("contentChanges")
+                .and_then(Json::as_array)
+                .and_then(|changes| changes.last())
+                .and_then(|change| change.get("text"))
+                .and_then(Json::as_str),
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                return vec![Reply::Send(publish_diagnostics(&uri, None))];
+            }
+            _ => return vec![],
+        };
+
+        let Some(text) = text else {
+            return vec![];
+        };
+        let document = Document::new(text);
+        let reply = publish_diagnostics(&uri, Some(&document));
+        self.documents.insert(uri, document);
+
+        vec![Reply::Send(reply)]
+    }
+
+    /// The open document and the position a request refers to.
+    fn target<'a>(&'a self, params: &'a Json) -> Option<(&'a str, &'a Document, Position)> {
+        let uri = params.get("textDocument")?.get("uri")?.as_str()?;
+        let position = params.get("position")?;
+        let position = Position {
+            line: position.get("line")?.as_f64()? as usize,
+            character: position.get("character")?.as_f64()? as usize,
+        };
+
+        Some((uri, self.documents.get(uri)?, position))
+    }
+
+    fn hover(&self, params: &Json) -> Json {
+        let Some((_, document, position)) = self.target(params) else {
+            return Json::Null;
+        };
+        let Some(token) = document.token_at(position) else {
+            return Json::Null;
+        };
+
+        let text = match document.declaration_of(token) {
+            Some(index) => {
+                let declaration = &document.declarations[index];
+                format!("var {}: {}", declaration.token.lexeme, declaration.kind)
+            }
+            None => match token.token_type {
+                TokenType::NUMBER => String::from("number"),
+                TokenType::BIGINT => String::from("bigint"),
+                TokenType::STRING => String::from("string"),
+                TokenType::TRUE | TokenType::FALSE => String::from("bool"),
+                TokenType::NIL => String::from("nil"),
+                _ => return Json::Null,
+            },
+        };
+
+        Json::object(vec![
+            (
+                "contents",
+                Json::object(vec![
+                    ("kind", Json::from("markdown")),
+                    ("value", Json::from(format!("```lox\n{}\n```", text))),
+                ]),
+            ),
+            ("range", range_json(document.token_range(token))),
+        ])
+    }
+
+    fn definition(&self, params: &Json) -> Json {
+        let Some((uri, document, position)) = self.target(params) else {
+            return Json::Null;
+        };
+
+        let declaration = document
+            .token_at(position)
+            .and_then(|token| document.declaration_of(token));
+        match declaration {
+            Some(index) => location(
+                uri,
+                document.token_range(&document.declarations[index].token),
+            ),
+            None => Json::Null,
+        }
+    }
+
+    fn references(&self, params: &Json) -> Json {
+        let Some((uri, document, position)) = self.target(params) else {
+            return Json::Null;
+        };
+        let Some(index) = document
+            .token_at(position)
+            .and_then(|token| document.declaration_of(token))
+        else {
+            return Json::Array(vec![]);
+        };
+
+        let include_declaration = params
+            .get("context")
+            .and_then(|context| context.get("includeDeclaration"))
+            == Some(&Json::Bool(true));
+        let mut tokens = document.references_to(index);
+        if include_declaration {
+            tokens.insert(0, &document.declarations[index].token);
+        }
+
+        Json::Array(
+            tokens
+                .into_iter()
+                .map(|token| location(uri, document.token_range(token)))
+                .collect(),
+        )
+    }
+
+    fn symbols(&self, params: &Json) -> Json {
+        let document = params
+            .get("textDocument")
+            .and_then(|doc| doc.get("uri"))
+            .and_then(Json::as_str)
+            .and_then(|uri| self.documents.get(uri));
+        let Some(document) = document else {
+            return Json::Null;
+        };
+
+        Json::Array(
+            document
+                .declarations
+                .iter()
+                .map(|declaration| {
+                    let range = range_json(document.token_range(&declaration.token));
+                    Json::object(vec![
+                        ("name", Json::from(declaration.token.lexeme.as_str())),
+                        ("detail", Json::from(declaration.kind.to_string())),
+                        ("kind", Json::from(SYMBOL_KIND_VARIABLE)),
+                        ("range", range.clone()),
+                        ("selectionRange", range),
+                    ])
+                })
+                .collect(),
+        )
+    }
+
+    fn semantic_tokens(&self, params: &Json) -> Json {
+        let document = params
+            .get("textDocument")
+            .and_then(|doc| doc.get("uri"))
+            .and_then(Json::as_str)
+            .and_then(|uri| self.documents.get(uri));
+
+        match document {
+            Some(document) => Json::object(vec![("data", Json::from(document.semantic_tokens()))]),
+            None => Json::Null,
+        }
+    }
+}
+
+fn capabilities() -> Json {
+    Json::object(vec![
+        (
+            "capabilities",
+            Json::object(vec![
+                // full document sync
+                ("textDocumentSync", Json::from(1u32)),
+                ("hoverProvider", Json::from(true)),
+                ("definitionProvider", Json::from(true)),
+                ("referencesProvider", Json::from(true)),
+                ("documentSymbolProvider", Json::from(true)),
+                (
+                    "semanticTokensProvider",
+                    Json::object(vec![
+                        (
+                            "legend",
+                            Json::object(vec![
+                                ("tokenTypes", Json::from(TOKEN_TYPES.to_vec())),
+                                ("tokenModifiers", Json::Array(vec![])),
+                            ]),
+                        ),
+                        ("full", Json::from(true)),
+                    ]),
+                ),
+            ]),
+        ),
+        (
+            "serverInfo",
+            Json::object(vec![
+                ("name", Json::from("lox")),
+                ("version", Json::from(env!("CARGO_PKG_VERSION"))),
+            ]),
+        ),
+    ])
+}
+
+fn publish_diagnostics(uri: &str, document: Option<&Document>) -> Json {
+    let diagnostics = document.map_or(vec![], |document| {
+        document
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                Json::object(vec![
+                    ("range", range_json(diagnostic.range)),
+                    // 1 is an error and 2 a warning
+                    (
+                        "severity",
+                        Json::from(if diagnostic.warning { 2u32 } else { 1 }),
+                    ),
+                    ("source", Json::from("lox")),
+                    ("message", Json::from(diagnostic.message.as_str())),
+                ])
+            })
+            .collect()
+    });
+
+    Json::object(vec![
+        ("jsonrpc", Json::from("2.0")),
+        ("method", Json::from("textDocument/publishDiagnostics")),
+        (
+            "params",
+            Json::object(vec![
+                ("uri", Json::from(uri)),
+                ("diagnostics", Json::Array(diagnostics)),
+            ]),
+        ),
+    ])
+}
+
+fn error_response(id: Json, code: f64, message: &str) -> Json {
+    Json::object(vec![
+        ("jsonrpc", Json::from("2.0")),
+        ("id", id),
+        (
+            "error",
+            Json::object(vec![
+                ("code", Json::from(code)),
+                ("message", Json::from(message)),
+            ]),
+        ),
+    ])
+}
+
+fn location(uri: &str, range: Range) -> Json {
+    Json::object(vec![("uri", Json::from(uri)), ("range", range_json(range))])
+}
+
+fn range_json(range: Range) -> Json {
+    let position = |position: Position| {
+        Json::object(vec![
+            ("line", Json::from(position.line)),
+            ("character", Json::from(position.character)),
+        ])
+    };
+
+    Json::object(vec![
+        ("start", position(range.start)),
+        ("end", position(range.end)),
+    ])
+}
@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod tests {
+    use crate::json::Json;
+    use crate::lsp::serve;
+    use std::io::{Cursor, ErrorKind};
+
+    fn frame(messages: &[&str]) -> Vec<u8> {
+        let mut input = String::new();
+        for message in messages {
+            input.push_str(&format!(
+                "Content-Length: {}\r\n\r\n{}",
+                message.len(),
+                message
+            ));
+        }
+
+        input.into_bytes()
+    }
+
+    fn exchange(messages: &[&str]) -> Vec<Json> {
+        let mut output = vec![];
+        serve(Cursor::new(frame(messages)), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        output
+            .split("Content-Length: ")
+            .filter(|part| !part.is_empty())
+            .map(|part| Json::parse(part.split_once("\r\n\r\n").unwrap().1).unwrap())
+            .collect()
+    }
+
+    fn open(text: &str) -> String {
+        format!(
+            r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"file:///a.lox","languageId":"lox","version":1,"text":{}}}}}}}"#,
+            Json::from(text)
+        )
+    }
+
+    fn request(id: u32, method: &str, line: u32, character: u32) -> String {
+        format!(
+            r#"{{"jsonrpc":"2.0","id":{},"method":"{}","params":{{"textDocument":{{"uri":"file:///a.lox"}},"position":{{"line":{},"character":{}}},"context":{{"includeDeclaration":true}}}}}}"#,
+            id, method, line, character
+        )
+    }
+
+    #[test]
+    fn initializes_and_shuts_down() {
+        let replies = exchange(&[
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
+            r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"shutdown"}"#,
+            r#"{"jsonrpc":"2.0","method":"exit"}"#,
+            r#"{"jsonrpc":"2.0","id":3,"method":"shutdown"}"#,
+        ]);
+        assert_eq!(replies.len(), 2);
+        let capabilities = replies[0]
+            .get("result")
+            .unwrap()
+            .get("capabilities")
+            .unwrap();
+        assert_eq!(capabilities.get("hoverProvider"), Some(&Json::Bool(true)));
+        assert_eq!(replies[1].get("result"), Some(&Json::Null));
+    }
+
+    #[test]
+    fn publishes_parse_errors() {
+        let replies = exchange(&[&open("var a = 1;\nprint a")]);
+        let diagnostics = replies[0]
+            .get("params")
+            .unwrap()
+            .get("diagnostics")
+            .unwrap();
+        assert_eq!(
+            diagnostics.to_string(),
+            r#"[{"range":{"start":{"line":1,"character":0},"end":{"line":1,"character":7}},"severity":1,"source":"lox","message":"Error at '': Missing ';'."}]"#
+        );
+    }
+
+    #[test]
+    fn navigates_variables() {
+        let source =
+            "var count = 1;\n{\n  var count = \"s\";\n  print count;\n}\ncount = count + 1;";
+        let replies = exchange(&[
+            &open(source),
+            &request(1, "textDocument/hover", 3, 9),
+            &request(2, "textDocument/definition", 5, 9),
+            &request(3, "textDocument/references", 0, 4),
+            &request(4, "textDocument/documentSymbol", 0, 0),
+            &request(5, "textDocument/semanticTokens/full", 0, 0),
+        ]);
+        assert_eq!(
+            replies[0].get("params").unwrap().get("diagnostics"),
+            Some(&Json::Array(vec![]))
+        );
+
+        let hover = replies[1].get("result").unwrap().get("contents").unwrap();
+        assert_eq!(
+            hover.get("value").and_then(Json::as_str),
+            Some("```lox\nvar count: string\n```")
+        );
+
+        let definition = replies[2].get("result").unwrap();
+        assert_eq!(
+            definition.get("range").unwrap().to_string(),
+            r#"{"start":{"line":0,"character":4},"end":{"line":0,"character":9}}"#
+        );
+
+        let lines: Vec<f64> = replies[3]
+            .get("result")
+            .and_then(Json::as_array)
+            .unwrap()
+            .iter()
+            .map(|location| {
+                let start = location.get("range").unwrap().get("start").unwrap();
+                start.get("line").and_then(Json::as_f64).unwrap()
+            })
+            .collect();
+        assert_eq!(lines, [0.0, 5.0, 5.0]);
+
+        let symbols = replies[4].get("result").and_then(Json::as_array).unwrap();
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(
+            symbols[1].get("detail").and_then(Json::as_str),
+            Some("string")
+        );
+
+        // var (keyword) count (variable) = (operator) 1 (number)
+        let data = replies[5]
+            .get("result")
+            .unwrap()
+            .get("data")
+            .unwrap()
+            .to_string();
+        assert!(data.starts_with("[0,0,3,0,0,0,4,5,1,0,0,6,1,5,0,0,2,1,4,0,"));
+    }
+
+    #[test]
+    fn unknown_requests_get_an_error() {
+        let replies = exchange(&[
+            r#"{"jsonrpc":"2.0","id":7,"method":"textDocument/rename","params":{}}"#,
+            "not json",
+        ]);
+        assert_eq!(
+            replies[0].get("error").unwrap().get("code"),
+            Some(&Json::Number(-32601.0))
+        );
+        assert_eq!(replies[1].get("id"), Some(&Json::Null));
+    }
+
+    #[test]
+    fn oversized_messages_are_rejected() {
+        let input = "Content-Length: 18446744073709551615\r\n\r\n{}";
+        let error = serve(Cursor::new(input), &mut vec![]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -1,17 +1,58 @@
-use codecrafters_interpreter::{Expr, Token};
-use std::io::{self, Write};
-use std::{env, fs, process::exit};
-
-mod parser;
-use parser::Parser;
-mod evaluate;
-use evaluate::{Eval, Stateless};
-mod runner;
-use runner::State;
-mod tokenizer;
+use codecrafters_interpreter::{
+    highlight, lint, parse, parse_expression, program_json, program_sexpr, run_tests, serve_lsp,
+    set_error_format, tokenize, tokenize_lossless, tokens_json, typecheck, ErrorFormat,
+    HighlightFormat, Interpreter, Limits, LintConfig,
+};
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::time::Duration;
+use std::{env, fs, process::exit, thread};
+
+/// Stack size of the thread running every command. The parser, evaluator and tools recurse, and
+/// their nesting limits are chosen to fit in this much stack in debug builds, which the main
+/// thread's platform default doesn't guarantee.
+const STACK_SIZE: usize = 64 * 1024 * 1024;
 
 fn main() {
+    let cli = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(cli)
+        .unwrap();
+    exit(match cli.join() {
+        Ok(()) => 0,
+        Err(_) => 101,
+    });
+}
+
+fn cli() {
     let args: Vec<String> = env::args().collect();
+    if args.get(1).map(String::as_str) == Some("lsp") {
+        let stdin = io::stdin();
+        exit(match serve_lsp(stdin.lock(), io::stdout()) {
+            Ok(()) => 0,
+            Err(err) => {
+                eprintln!("Language server stopped: {}", err);
+                74
+            }
+        });
+    }
+
+    if args.get(1).map(String::as_str) == Some("test") && args.len() >= 3 {
+        exit(match run_tests(Path::new(&args[2])) {
+            Ok(summary) => {
+                println!("{}", summary);
+                match summary.failed() {
+                    0 => 0,
+                    _ => 1,
+                }
+            }
+            Err(err) => {
+                eprintln!("Failed to read tests in {}: {}", args[2], err);
+                64
+            }
+        });
+    }
+
     if args.len() < 3 {
         writeln!(io::stderr(), "Usage: {} tokenize <filename>", args[0]).unwrap();
         return;
@@ -19,6 +60,15 @@ fn main() {
 
     writeln!(io::stderr(), "Logs from your program will appear here!").unwrap();
 
+    if let Some(name) = get_flag(&args[3..], "--error-format") {
+        let Some(format) = ErrorFormat::from_name(&name) else {
+            eprintln!("Unknown error format: {}", name);
+            exit(64);
+        };
+        let colour = io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none();
+        set_error_format(format, colour);
+    }
+
     let command = &args[1];
     let filename = &args[2];
     let file_contents = fs::read_to_string(filename).unwrap_or_else(|_| {
@@ -28,6 +78,17 @@ fn main() {
     let mut status_code = 0;
 
     match command.as_str() {
+        "tokenize" if get_flag(&args[3..], "--format").is_some() => {
+            let format = get_flag(&args[3..], "--format").unwrap_or_default();
+            if format != "json" {
+                eprintln!("Unknown token format: {}", format);
+                exit(64);
+            }
+
+            let (tokens, status_code) = tokenize_lossless(&file_contents);
+            println!("{}", tokens_json(&tokens));
+            exit(status_code);
+        }
         "tokenize" => {
             let (tokens, status_code) = tokenize(file_contents);
             for token in tokens {
@@ -36,38 +97,77 @@ fn main() {
 
             exit(status_code);
         }
+        "parse" | "test-parse" if get_flag(&args[3..], "--format").is_some() => {
+            if let Err(code) = dump_ast(&file_contents, &args[3..]) {
+                status_code = code;
+            }
+
+            exit(status_code);
+        }
         "parse" => {
-            match parse_expression(file_contents) {
+            match parse_expression(&file_contents) {
                 Ok(expr) => println!("{}", expr),
-                Err(_) => status_code = 65,
+                Err(err) => status_code = err.exit_code(),
             }
 
             exit(status_code);
         }
         "evaluate" => {
-            match evaluate(file_contents) {
-                Ok(token) => token.print(),
-                Err(code) => status_code = code,
+            match Interpreter::new().eval_expr(&file_contents) {
+                Ok(value) => println!("{}", value),
+                Err(_) => status_code = 70,
             }
 
             exit(status_code);
         }
         "run" => {
-            match run(file_contents) {
-                Err(code) => status_code = code,
-                _ => {}
+            if let Err(code) = run(&file_contents, filename, &args[3..]) {
+                status_code = code;
+            }
+
+            exit(status_code);
+        }
+        "debug" => {
+            if let Err(code) = debug(&file_contents, filename, &args[3..]) {
+                status_code = code;
+            }
+
+            exit(status_code);
+        }
+        "typecheck" => {
+            if let Err(err) = typecheck(&file_contents) {
+                status_code = err.exit_code();
+            }
+
+            exit(status_code);
+        }
+        "lint" => {
+            if let Err(code) = lint_file(&file_contents, filename, &args[3..]) {
+                status_code = code;
+            }
+
+            exit(status_code);
+        }
+        "highlight" => {
+            let name = get_flag(&args[3..], "--format").unwrap_or(String::from("ansi"));
+            match HighlightFormat::from_name(&name) {
+                Some(format) => print!("{}", highlight(&file_contents, format)),
+                None => {
+                    eprintln!("Unknown highlight format: {}", name);
+                    status_code = 64;
+                }
             }
 
             exit(status_code);
         }
         "test-parse" => {
-            match parse(file_contents) {
+            match parse(&file_contents) {
                 Ok(exprs) => {
                     for expr in exprs {
                         println!("{expr}");
                     }
                 }
-                Err(code) => status_code = code,
+                Err(err) => status_code = err.exit_code(),
             }
             exit(status_code);
         }
@@ -78,47 +178,165 @@ fn main() {
     }
 }
 
-fn tokenize(file_contents: String) -> (Vec<Token>, i32) {
-    tokenizer::tokenize(file_contents)
-}
+fn run(file_contents: &str, filename: &str, flags: &[String]) -> Result<(), i32> {
+    let mut interpreter = configure(flags)?;
+    interpreter.set_file(filename);
+    let coverage_path = get_flag(flags, "--coverage");
+    if coverage_path.is_some() {
+        interpreter.enable_coverage();
+    }
+
+    let folded_path = get_flag(flags, "--profile-folded");
+    if has_flag(flags, "--profile") || folded_path.is_some() {
+        interpreter.enable_profiling();
+    }
+
+    let res = interpreter
+        .run_source(file_contents)
+        .map_err(|err| err.exit_code());
+    if let Some(profile) = interpreter.profile() {
+        if has_flag(flags, "--profile") {
+            eprint!("{}", profile.report(file_contents));
+        }
 
-fn parse(file_contents: String) -> Result<Vec<Expr>, i32> {
-    let (tokens, status_code) = tokenize(file_contents);
-    if status_code != 0 {
-        return Err(65);
+        if let Some(path) = folded_path {
+            if fs::write(&path, profile.folded()).is_err() {
+                eprintln!("Failed to write profile to {}", path);
+                return Err(74);
+            }
+        }
     }
-    let mut parser = Parser::new(tokens);
-    if let Ok(expressions) = parser.parse() {
-        return Ok(expressions);
+
+    if let (Some(coverage), Some(path)) = (interpreter.coverage(), coverage_path) {
+        let source_file = fs::canonicalize(filename)
+            .map_or(filename.to_string(), |path| path.display().to_string());
+        if fs::write(&path, coverage.lcov(&source_file)).is_err() {
+            eprintln!("Failed to write coverage to {}", path);
+            return Err(74);
+        }
     }
 
-    Err(65)
+    res
 }
 
-fn parse_expression(file_contents: String) -> Result<Expr, ()> {
-    let (tokens, _) = tokenize(file_contents);
-    let mut parser = Parser::new(tokens);
-
-    parser.parse_assignment()
+fn debug(file_contents: &str, filename: &str, flags: &[String]) -> Result<(), i32> {
+    let mut interpreter = configure(flags)?;
+    interpreter.set_file(filename);
+    interpreter
+        .debug_source(file_contents, io::stdin().lock())
+        .map_err(|err| err.exit_code())
 }
 
-fn evaluate(file_contents: String) -> Result<Token, i32> {
-    if let Ok(expr) = parse_expression(file_contents) {
-        if let Ok(token) = Stateless::evaluate(&mut Stateless, expr) {
-            return Ok(token);
+/// Lints a script with the rules from `--config`, or from a `.loxlint` file next to the script.
+/// Exits with 1 when there are warnings.
+fn lint_file(file_contents: &str, filename: &str, flags: &[String]) -> Result<(), i32> {
+    let default_config = Path::new(filename)
+        .parent()
+        .map(|dir| dir.join(".loxlint"))
+        .filter(|path| path.exists());
+    let config_path = get_flag(flags, "--config")
+        .or_else(|| default_config.map(|path| path.display().to_string()));
+    let config = match config_path {
+        Some(path) => {
+            let text = fs::read_to_string(&path).map_err(|_| {
+                eprintln!("Failed to read lint config {}", path);
+                64
+            })?;
+            LintConfig::parse(&text).map_err(|err| {
+                eprintln!("{}: {}.", path, err);
+                64
+            })?
         }
+        None => LintConfig::default(),
+    };
+
+    let warnings = lint(file_contents, &config).map_err(|err| err.exit_code())?;
+    for warning in warnings.iter() {
+        println!("{}", warning);
+    }
+
+    match warnings.is_empty() {
+        true => Ok(()),
+        false => Err(1),
+    }
+}
+
+/// Prints the whole program's syntax tree for other tools, as versioned JSON or an S-expression.
+fn dump_ast(file_contents: &str, flags: &[String]) -> Result<(), i32> {
+    let format = get_flag(flags, "--format").unwrap_or_default();
+    if format != "json" && format != "sexpr" {
+        eprintln!("Unknown AST format: {}", format);
+        return Err(64);
+    }
+
+    let program = parse(file_contents).map_err(|err| err.exit_code())?;
+    match format.as_str() {
+        "json" => println!("{}", program_json(&program)),
+        _ => println!("{}", program_sexpr(&program)),
     }
 
-    Err(70)
+    Ok(())
 }
 
-fn run(file_contents: String) -> Result<(), i32> {
-    let expressions = parse(file_contents)?;
-    let mut program_state = State::new();
-    if let Ok(_) = program_state.run(expressions) {
-        return Ok(());
+fn configure(flags: &[String]) -> Result<Interpreter, i32> {
+    let mut interpreter = Interpreter::new();
+    if let Some(val) = get_flag(flags, "--seed") {
+        interpreter.seed_random(parse_number_flag("--seed", &val)?);
+    }
+
+    let mut limits = Limits::default();
+    if let Some(val) = get_flag(flags, "--max-steps") {
+        limits.max_steps = Some(parse_number_flag("--max-steps", &val)?);
+    }
+
+    if let Some(val) = get_flag(flags, "--max-depth") {
+        limits.max_depth = parse_number_flag("--max-depth", &val)? as usize;
+    }
+
+    if let Some(val) = get_flag(flags, "--timeout") {
+        limits.timeout = Some(Duration::from_millis(parse_number_flag("--timeout", &val)?));
+    }
+    interpreter.set_limits(limits);
+
+    if let Some(dir) = get_flag(flags, "--allow-read") {
+        interpreter
+            .allow_read(&dir)
+            .map_err(|err| err.exit_code())?;
+    }
+
+    if let Some(dir) = get_flag(flags, "--allow-write") {
+        interpreter
+            .allow_write(&dir)
+            .map_err(|err| err.exit_code())?;
+    }
+
+    Ok(interpreter)
+}
+
+fn parse_number_flag(name: &str, val: &str) -> Result<u64, i32> {
+    val.parse::<u64>().map_err(|_| {
+        eprintln!("Invalid value for {}: {}", name, val);
+        64
+    })
+}
+
+fn has_flag(args: &[String], name: &str) -> bool {
+    args.iter().any(|arg| arg == name)
+}
+
+/// Looks up an option given as either `--name value` or `--name=value`.
+fn get_flag(args: &[String], name: &str) -> Option<String> {
+    let prefix = format!("{}=", name);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == name {
+            return iter.next().cloned();
+        }
+
+        if let Some(val) = arg.strip_prefix(&prefix) {
+            return Some(val.to_string());
+        }
     }
 
-    //test comment
-    Err(70)
+    None
 }
@@ -0,0 +1,118 @@
+use crate::diagnostics;
+use std::cell::RefCell;
+use std::fmt;
+use std::io::{self, Write};
+use std::rc::Rc;
+mod tests;
+
+/// Where program output or diagnostics are written.
+pub type Sink = Rc<RefCell<dyn Write>>;
+
+thread_local! {
+    static OUTPUT: RefCell<Option<Sink>> = const { RefCell::new(None) };
+    static DIAGNOSTICS: RefCell<Option<Sink>> = const { RefCell::new(None) };
+}
+
+/// Writes a line of program output (what `print` shows).
+macro_rules! outln {
+    ($($arg:tt)*) => {
+        $crate::output::write_output(format_args!($($arg)*))
+    };
+}
+
+/// Writes a line of diagnostics, i.e. tokenizer, parser and runtime errors.
+macro_rules! errln {
+    ($($arg:tt)*) => {
+        $crate::output::write_diagnostic(format_args!($($arg)*))
+    };
+}
+
+pub(crate) use errln;
+pub(crate) use outln;
+
+pub fn write_output(args: fmt::Arguments) {
+    OUTPUT.with(|sink| match *sink.borrow() {
+        Some(ref sink) => {
+            let _ = writeln!(sink.borrow_mut(), "{}", args);
+        }
+        None => println!("{}", args),
+    });
+}
+
+pub fn write_diagnostic(args: fmt::Arguments) {
+    write_diagnostic_text(&diagnostics::plain(args.to_string()));
+}
+
+/// Writes an already formatted diagnostic.
+pub fn write_diagnostic_text(text: &str) {
+    DIAGNOSTICS.with(|sink| match *sink.borrow() {
+        Some(ref sink) => {
+            let _ = writeln!(sink.borrow_mut(), "{}", text);
+        }
+        None => eprintln!("{}", text),
+    });
+}
+
+/// The sinks an interpreter writes to. `None` means the process' stdout/stderr.
+#[derive(Default, Clone)]
+pub struct Sinks {
+    pub output: Option<Sink>,
+    pub diagnostics: Option<Sink>,
+}
+
+impl Sinks {
+    /// Routes `outln!`/`errln!` to these sinks until the returned guard is dropped.
+    pub fn install(&self) -> SinkGuard {
+        let output = OUTPUT.with(|sink| sink.replace(self.output.clone()));
+        let diagnostics = DIAGNOSTICS.with(|sink| sink.replace(self.diagnostics.clone()));
+
+        SinkGuard {
+            output,
+            diagnostics,
+        }
+    }
+}
+
+/// Restores the previously installed sinks, so nested interpreters don't clobber each other.
+pub struct SinkGuard {
+    output: Option<Sink>,
+    diagnostics: Option<Sink>,
+}
+
+impl Drop for SinkGuard {
+    fn drop(&mut self) {
+        OUTPUT.with(|sink| sink.replace(self.output.take()));
+        DIAGNOSTICS.with(|sink| sink.replace(self.diagnostics.take()));
+    }
+}
+
+/// An in-memory sink, handy for capturing script output in tests. Clones share the same buffer.
+#[derive(Default, Clone)]
+pub struct SharedBuffer {
+    buffer: Rc<RefCell<Vec<u8>>>,
+}
+
+impl SharedBuffer {
+    pub fn new() -> Self {
+        SharedBuffer::default()
+    }
+
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.buffer.borrow()).into_owned()
+    }
+
+    pub fn clear(&self) {
+        self.buffer.borrow_mut().clear();
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+    use crate::output::{errln, outln, SharedBuffer, Sink, Sinks};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn installed_sinks_capture_lines() {
+        let out = SharedBuffer::new();
+        let err = SharedBuffer::new();
+        let sinks = Sinks {
+            output: Some(Rc::new(RefCell::new(out.clone())) as Sink),
+            diagnostics: Some(Rc::new(RefCell::new(err.clone())) as Sink),
+        };
+
+        {
+            let _guard = sinks.install();
+            outln!("{} {}", "hello", 1);
+            errln!("[line {}] oops", 2);
+        }
+
+        assert_eq!(out.contents(), "hello 1\n");
+        assert_eq!(err.contents(), "[line 2] oops\n");
+    }
+
+    #[test]
+    fn nested_installs_restore_the_outer_sink() {
+        let outer = SharedBuffer::new();
+        let inner = SharedBuffer::new();
+        let outer_sinks = Sinks {
+            output: Some(Rc::new(RefCell::new(outer.clone())) as Sink),
+            diagnostics: None,
+        };
+        let inner_sinks = Sinks {
+            output: Some(Rc::new(RefCell::new(inner.clone())) as Sink),
+            diagnostics: None,
+        };
+
+        let _outer_guard = outer_sinks.install();
+        {
+            let _inner_guard = inner_sinks.install();
+            outln!("inner");
+        }
+        outln!("outer");
+
+        assert_eq!(inner.contents(), "inner\n");
+        assert_eq!(outer.contents(), "outer\n");
+    }
+}
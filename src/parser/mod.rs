@@ -1,14 +1,54 @@
-use codecrafters_interpreter::{Conditional, Expr, Statement, Token, TokenType};
+use crate::diagnostics::{report, Diagnostic};
+use crate::typecheck::Type;
+use crate::{Conditional, Expr, MatchArm, Pattern, Statement, Token, TokenType};
 mod tests;
 
+/// Deepest nesting of blocks/expressions accepted. The parser and evaluator are recursive, so
+/// without a cap a pathological script would overflow the stack and abort the process. Every
+/// group passes through each precedence level, so this is sized for the deepest script to still
+/// fit in a debug build's 8 MB main thread, with the command line's bigger stack as headroom.
+const MAX_NESTING: usize = 256;
+
+/// Recursive descent parser. Operators from loosest to tightest binding:
+///
+/// | Operators                          | Associativity |
+/// |------------------------------------|---------------|
+/// | `=` `+=` `-=` `*=` `/=`            | right         |
+/// | `?:`                               | right         |
+/// | `or`                               | left          |
+/// | `and`                              | left          |
+/// | `==` `!=`                          | left          |
+/// | `<` `<=` `>` `>=`                  | left          |
+/// | `\|`                               | left          |
+/// | `^`                                | left          |
+/// | `&`                                | left          |
+/// | `<<` `>>`                          | left          |
+/// | `+` `-` `+%` `-%`                  | left          |
+/// | `*` `/` `%` `div` `*%`             | left          |
+/// | unary `!` `-` `~`                  | right         |
+/// | `**`                               | right         |
+/// | prefix `++` `--`                   | right         |
+/// | calls, indexing, postfix `++` `--` | left          |
 pub struct Parser {
     pub tokens: Vec<Token>,
     current: usize,
+    nesting: usize,
+    /// Problems in a program that still parses, left for the caller to report.
+    warnings: Vec<Diagnostic>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, current: 0 }
+        Parser {
+            tokens,
+            current: 0,
+            nesting: 0,
+            warnings: vec![],
+        }
+    }
+
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
     }
 
     pub fn parse(&mut self) -> Result<Vec<Expr>, ()> {
@@ -30,13 +70,26 @@ impl Parser {
     }
 
     pub fn parse_expression(&mut self) -> Result<Expr, ()> {
+        self.nested(Self::statement)
+    }
+
+    fn statement(&mut self) -> Result<Expr, ()> {
+        if self.curr_matches_type(TokenType::LEFT_BRACE) {
+            // a brace in statement position always opens a block, never a map literal
+            let brace = self.tokens[self.current].clone();
+            self.current += 1;
+            let exprs = self.parse_scope(&brace)?;
+            return Ok(Expr::Scope(brace, exprs));
+        }
+
         let expr = self.parse_assignment()?;
         if matches!(
             expr,
-            Expr::Scope(_)
+            Expr::Scope(..)
                 | Expr::Stmt(Statement::IfStmt(_))
                 | Expr::Stmt(Statement::ForStmt(..))
                 | Expr::Stmt(Statement::WhileStmt(_))
+                | Expr::Stmt(Statement::MatchStmt(..))
         ) {
             return Ok(expr);
         }
@@ -50,8 +103,30 @@ impl Parser {
     }
 
     pub fn parse_assignment(&mut self) -> Result<Expr, ()> {
+        self.nested(Self::assignment)
+    }
+
+    fn assignment(&mut self) -> Result<Expr, ()> {
         // check if the start is an identifier if it followed by EQUAL token
-        let mut expr = self.parse_or()?;
+        let mut expr = self.parse_ternary()?;
+        let operator = self.tokens[self.current].clone();
+        if matches!(
+            operator.token_type,
+            TokenType::PLUS_EQUAL
+                | TokenType::MINUS_EQUAL
+                | TokenType::STAR_EQUAL
+                | TokenType::SLASH_EQUAL
+        ) {
+            self.assignable(&expr, &operator)?;
+            self.current += 1;
+            let value = self.parse_assignment()?;
+            return Ok(Expr::Stmt(Statement::CompoundAssignmentStmt(
+                Box::new(expr),
+                operator,
+                Box::new(value),
+            )));
+        }
+
         while self.curr_matches_type(TokenType::EQUAL) {
             match expr {
                 Expr::Literal(token) => {
@@ -62,6 +137,16 @@ impl Parser {
                     let value = self.parse_assignment()?;
                     expr = Expr::Stmt(Statement::AssignmentStmt(token, Box::new(value)));
                 }
+                Expr::Index(target, bracket, key) => {
+                    self.current += 1;
+                    let value = self.parse_assignment()?;
+                    expr = Expr::Stmt(Statement::IndexAssignmentStmt(
+                        target,
+                        bracket,
+                        key,
+                        Box::new(value),
+                    ));
+                }
                 _ => {
                     self.print_token_err("Cannot assign to non-identifier")?;
                 }
@@ -71,6 +156,30 @@ impl Parser {
         Ok(expr)
     }
 
+    /// `condition ? then : otherwise`, binding looser than `or` and grouping to the right.
+    fn parse_ternary(&mut self) -> Result<Expr, ()> {
+        let condition = self.parse_or()?;
+        if !self.curr_matches_type(TokenType::QUESTION) {
+            return Ok(condition);
+        }
+
+        let question = self.tokens[self.current].clone();
+        self.current += 1;
+        let then = self.parse_assignment()?;
+        if !self.curr_matches_type(TokenType::COLON) {
+            self.print_unclosed_err("Missing ':'", &question, "to match this '?'")?;
+        }
+
+        self.current += 1;
+        let otherwise = self.nested(Self::parse_ternary)?;
+        Ok(Expr::Ternary(
+            Box::new(condition),
+            question,
+            Box::new(then),
+            Box::new(otherwise),
+        ))
+    }
+
     fn parse_or(&mut self) -> Result<Expr, ()> {
         let mut expr = self.parse_and()?;
         while self.curr_matches_type(TokenType::OR) {
@@ -111,10 +220,61 @@ impl Parser {
     }
 
     fn parse_comparison(&mut self) -> Result<Expr, ()> {
-        let mut expr = self.parse_additive()?;
+        let mut expr = self.parse_bit_or()?;
         while matches!(
             self.tokens[self.current].token_type,
             TokenType::GREATER | TokenType::GREATER_EQUAL | TokenType::LESS | TokenType::LESS_EQUAL
+        ) {
+            let operator = &self.tokens[self.current].clone();
+            self.current += 1;
+            let right = self.parse_bit_or()?;
+            expr = Expr::Binary(Box::new(expr), operator.clone(), Box::new(right));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_bit_or(&mut self) -> Result<Expr, ()> {
+        let mut expr = self.parse_bit_xor()?;
+        while self.curr_matches_type(TokenType::PIPE) {
+            let operator = &self.tokens[self.current].clone();
+            self.current += 1;
+            let right = self.parse_bit_xor()?;
+            expr = Expr::Binary(Box::new(expr), operator.clone(), Box::new(right));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_bit_xor(&mut self) -> Result<Expr, ()> {
+        let mut expr = self.parse_bit_and()?;
+        while self.curr_matches_type(TokenType::CARET) {
+            let operator = &self.tokens[self.current].clone();
+            self.current += 1;
+            let right = self.parse_bit_and()?;
+            expr = Expr::Binary(Box::new(expr), operator.clone(), Box::new(right));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_bit_and(&mut self) -> Result<Expr, ()> {
+        let mut expr = self.parse_shift()?;
+        while self.curr_matches_type(TokenType::AMPERSAND) {
+            let operator = &self.tokens[self.current].clone();
+            self.current += 1;
+            let right = self.parse_shift()?;
+            expr = Expr::Binary(Box::new(expr), operator.clone(), Box::new(right));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_shift(&mut self) -> Result<Expr, ()> {
+        let mut expr = self.parse_additive()?;
+        while matches!(
+            self.tokens[self.current].token_type,
+            TokenType::LESS_LESS | TokenType::GREATER_GREATER
         ) {
             let operator = &self.tokens[self.current].clone();
             self.current += 1;
@@ -129,7 +289,7 @@ impl Parser {
         let mut expr = self.parse_multiplicative()?;
         while matches!(
             self.tokens[self.current].token_type,
-            TokenType::PLUS | TokenType::MINUS
+            TokenType::PLUS | TokenType::MINUS | TokenType::PLUS_PERCENT | TokenType::MINUS_PERCENT
         ) {
             let operator = &self.tokens[self.current].clone();
             self.current += 1;
@@ -144,7 +304,11 @@ impl Parser {
         let mut expr = self.parse_unary()?;
         while matches!(
             self.tokens[self.current].token_type,
-            TokenType::STAR | TokenType::SLASH
+            TokenType::STAR
+                | TokenType::SLASH
+                | TokenType::PERCENT
+                | TokenType::DIV
+                | TokenType::STAR_PERCENT
         ) {
             let operator = &self.tokens[self.current].clone();
             self.current += 1;
@@ -156,9 +320,13 @@ impl Parser {
     }
 
     fn parse_unary(&mut self) -> Result<Expr, ()> {
+        self.nested(Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ()> {
         let token = &self.tokens[self.current].clone();
         match token.token_type {
-            TokenType::BANG | TokenType::MINUS => {
+            TokenType::BANG | TokenType::MINUS | TokenType::TILDE => {
                 let operator = token;
                 self.current += 1;
                 let right = self.parse_unary()?;
@@ -167,7 +335,126 @@ impl Parser {
             _ => {}
         }
 
-        self.parse_primary_expr()
+        self.parse_power()
+    }
+
+    /// `base ** exponent`. The exponent may itself be a unary or power expression, so `**`
+    /// groups to the right and `-2 ** 2` is `-(2 ** 2)`.
+    fn parse_power(&mut self) -> Result<Expr, ()> {
+        let base = self.parse_prefix()?;
+        if !self.curr_matches_type(TokenType::STAR_STAR) {
+            return Ok(base);
+        }
+
+        let operator = self.tokens[self.current].clone();
+        self.current += 1;
+        let exponent = self.parse_unary()?;
+        Ok(Expr::Binary(Box::new(base), operator, Box::new(exponent)))
+    }
+
+    /// `++target` and `--target`. Without a target, `--` is two negations as in plain Lox.
+    fn parse_prefix(&mut self) -> Result<Expr, ()> {
+        let token = self.tokens[self.current].clone();
+        if !matches!(
+            token.token_type,
+            TokenType::PLUS_PLUS | TokenType::MINUS_MINUS
+        ) {
+            return self.parse_call();
+        }
+
+        let start = self.current;
+        self.current += 1;
+        // every target starts with a name, so `--(3)` and `----1` are negations without parsing
+        let negation = token.token_type == TokenType::MINUS_MINUS;
+        let target = match negation && !self.curr_matches_type(TokenType::IDENTIFIER) {
+            true => None,
+            false => Some(self.parse_call()?),
+        };
+        let Some(target) = target.filter(|target| !negation || is_assignable(target)) else {
+            self.current = start;
+            self.split_minus_minus();
+            return self.parse_unary();
+        };
+
+        self.assignable(&target, &token)?;
+        Ok(Expr::Stmt(Statement::IncrementStmt(
+            Box::new(target),
+            token,
+            false,
+        )))
+    }
+
+    fn parse_call(&mut self) -> Result<Expr, ()> {
+        let mut expr = self.parse_primary_expr()?;
+        loop {
+            match self.tokens[self.current].token_type {
+                TokenType::LEFT_PAREN => {
+                    let callee = match expr {
+                        Expr::Literal(ref token) if token.token_type == TokenType::IDENTIFIER => {
+                            token.clone()
+                        }
+                        _ => {
+                            self.print_token_err("Can only call functions by name")?;
+                            return Err(());
+                        }
+                    };
+
+                    let paren = self.tokens[self.current].clone();
+                    self.current += 1;
+                    let args = self.parse_arguments(&paren)?;
+                    expr = Expr::Call(callee, args);
+                }
+                TokenType::LEFT_BRACKET => {
+                    let bracket = self.tokens[self.current].clone();
+                    self.current += 1;
+                    let key = self.parse_assignment()?;
+                    if !self.curr_matches_type(TokenType::RIGHT_BRACKET) {
+                        self.print_unclosed_err("Missing ']'", &bracket, "index opened here")?;
+                    }
+
+                    self.current += 1;
+                    expr = Expr::Index(Box::new(expr), bracket, Box::new(key));
+                }
+                TokenType::MINUS_MINUS if !self.is_postfix_decrement(&expr) => {
+                    // `a--b` is a subtraction of a negation, as in plain Lox
+                    self.split_minus_minus();
+                    break;
+                }
+                TokenType::PLUS_PLUS | TokenType::MINUS_MINUS => {
+                    let operator = self.tokens[self.current].clone();
+                    self.assignable(&expr, &operator)?;
+                    self.current += 1;
+                    expr = Expr::Stmt(Statement::IncrementStmt(Box::new(expr), operator, true));
+                }
+                _ => break,
+            }
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_arguments(&mut self, paren: &Token) -> Result<Vec<Expr>, ()> {
+        let mut args: Vec<Expr> = vec![];
+        if self.curr_matches_type(TokenType::RIGHT_PAREN) {
+            self.current += 1;
+            return Ok(args);
+        }
+
+        loop {
+            args.push(self.parse_assignment()?);
+            if !self.curr_matches_type(TokenType::COMMA) {
+                break;
+            }
+
+            self.current += 1;
+        }
+
+        if !self.curr_matches_type(TokenType::RIGHT_PAREN) {
+            self.print_unclosed_err("Missing ')'", paren, "call opened here")?;
+        }
+
+        self.current += 1;
+        Ok(args)
     }
 
     fn parse_primary_expr(&mut self) -> Result<Expr, ()> {
@@ -179,17 +466,26 @@ impl Parser {
             | TokenType::NIL
             | TokenType::STRING
             | TokenType::NUMBER
+            | TokenType::BIGINT
             | TokenType::IDENTIFIER => {
                 return Ok(Expr::Literal(token.clone()));
             }
             TokenType::LEFT_BRACE => {
-                let exprs = self.parse_scope()?;
-                return Ok(Expr::Scope(exprs));
+                if self.is_map_literal() {
+                    let brace = token.clone();
+                    let entries = self.parse_map(&brace)?;
+                    return Ok(Expr::Map(brace, entries));
+                }
+
+                let brace = token.clone();
+                let exprs = self.parse_scope(&brace)?;
+                return Ok(Expr::Scope(brace, exprs));
             }
             TokenType::LEFT_PAREN => {
+                let paren = token.clone();
                 let expr = self.parse_assignment()?;
                 if !self.curr_matches_type(TokenType::RIGHT_PAREN) {
-                    self.print_token_err("Missing ')'")?;
+                    self.print_unclosed_err("Missing ')'", &paren, "opened here")?;
                 }
 
                 self.current += 1;
@@ -202,6 +498,19 @@ impl Parser {
             TokenType::VAR => {
                 return self.variable_declaration();
             }
+            TokenType::IMPORT => {
+                let (path, names) = self.handle_import_stmt()?;
+                return Ok(Expr::Stmt(Statement::ImportStmt(path, names)));
+            }
+            TokenType::EXPORT => {
+                if !self.curr_matches_type(TokenType::VAR) {
+                    self.print_token_err("Expected variable declaration after 'export'")?;
+                }
+
+                self.current += 1;
+                let declaration = self.variable_declaration()?;
+                return Ok(Expr::Stmt(Statement::ExportStmt(Box::new(declaration))));
+            }
             TokenType::IF => {
                 let conditionals = self.handle_if_stmt()?;
                 return Ok(Expr::Stmt(Statement::IfStmt(conditionals)));
@@ -210,6 +519,10 @@ impl Parser {
                 let conditional = self.handle_conditional()?;
                 return Ok(Expr::Stmt(Statement::WhileStmt(conditional)));
             }
+            TokenType::MATCH => {
+                let keyword = self.tokens[self.current - 1].clone();
+                return Ok(Expr::Stmt(self.handle_match_stmt(keyword)?));
+            }
             TokenType::FOR => {
                 let (var_init, condition, var_update, expr) = self.handle_for_stmt()?;
                 return Ok(Expr::Stmt(Statement::ForStmt(
@@ -228,7 +541,7 @@ impl Parser {
         Err(())
     }
 
-    fn parse_scope(&mut self) -> Result<Vec<Expr>, ()> {
+    fn parse_scope(&mut self, brace: &Token) -> Result<Vec<Expr>, ()> {
         let mut exprs: Vec<Expr> = vec![];
         loop {
             match self.tokens[self.current].token_type {
@@ -237,7 +550,7 @@ impl Parser {
                     break;
                 }
                 TokenType::EOF => {
-                    self.print_token_err("Missing '}}'")?;
+                    self.print_unclosed_err("Missing '}'", brace, "block opened here")?;
                 }
                 _ => {}
             }
@@ -249,6 +562,63 @@ impl Parser {
         Ok(exprs)
     }
 
+    /// Whether a `{` in expression position opens a map: it's `{}`, or a `:` follows the first
+    /// key before any `;` ends a statement. Colons closing a `?:` or nested inside brackets don't
+    /// count.
+    fn is_map_literal(&self) -> bool {
+        if self.curr_matches_type(TokenType::RIGHT_BRACE) {
+            return true;
+        }
+
+        let (mut depth, mut ternaries) = (0, 0);
+        for token in &self.tokens[self.current..] {
+            match token.token_type {
+                TokenType::LEFT_PAREN | TokenType::LEFT_BRACKET | TokenType::LEFT_BRACE => {
+                    depth += 1
+                }
+                TokenType::RIGHT_PAREN | TokenType::RIGHT_BRACKET | TokenType::RIGHT_BRACE
+                    if depth > 0 =>
+                {
+                    depth -= 1
+                }
+                _ if depth > 0 => {}
+                TokenType::QUESTION => ternaries += 1,
+                TokenType::COLON if ternaries > 0 => ternaries -= 1,
+                TokenType::COLON => return true,
+                TokenType::SEMICOLON | TokenType::RIGHT_BRACE | TokenType::EOF => return false,
+                _ => {}
+            }
+        }
+
+        false
+    }
+
+    fn parse_map(&mut self, brace: &Token) -> Result<Vec<(Expr, Expr)>, ()> {
+        let mut entries: Vec<(Expr, Expr)> = vec![];
+        loop {
+            if self.curr_matches_type(TokenType::RIGHT_BRACE) {
+                self.current += 1;
+                break;
+            }
+
+            let key = self.parse_assignment()?;
+            if !self.curr_matches_type(TokenType::COLON) {
+                self.print_token_err("Missing ':' after map key")?;
+            }
+
+            self.current += 1;
+            let value = self.parse_assignment()?;
+            entries.push((key, value));
+            match self.tokens[self.current].token_type {
+                TokenType::COMMA => self.current += 1,
+                TokenType::RIGHT_BRACE => {}
+                _ => self.print_unclosed_err("Missing '}'", brace, "map opened here")?,
+            }
+        }
+
+        Ok(entries)
+    }
+
     fn handle_if_stmt(&mut self) -> Result<Vec<Conditional>, ()> {
         // also deals with else if and else blocks
         let mut conditionals: Vec<Conditional> = vec![];
@@ -294,6 +664,148 @@ impl Parser {
         Ok(Conditional(Box::new(condition), Box::new(expr)))
     }
 
+    /// `match (value) { arm, ... }`. Each arm is `pattern | pattern if guard => statement`, and
+    /// the comma after it is optional.
+    fn handle_match_stmt(&mut self, keyword: Token) -> Result<Statement, ()> {
+        let value = self.parse_primary_expr()?;
+        match value {
+            Expr::Grouping(_) => {}
+            _ => self.print_token_err(
+                "Expected value to match (make sure to enclose within parentheses '()')",
+            )?,
+        }
+
+        if !self.curr_matches_type(TokenType::LEFT_BRACE) {
+            self.print_token_err("Expected '{' before match arms")?;
+        }
+        let brace = self.tokens[self.current].clone();
+        self.current += 1;
+
+        let mut arms: Vec<MatchArm> = vec![];
+        loop {
+            match self.tokens[self.current].token_type {
+                TokenType::RIGHT_BRACE => {
+                    self.current += 1;
+                    break;
+                }
+                TokenType::EOF => {
+                    self.print_unclosed_err("Missing '}'", &brace, "match opened here")?;
+                }
+                _ => {}
+            }
+
+            arms.push(self.match_arm()?);
+            if self.curr_matches_type(TokenType::COMMA) {
+                self.current += 1;
+            }
+        }
+
+        // not an error: like an `if` without `else`, a value no arm matches does nothing
+        if !arms.iter().any(MatchArm::is_catch_all) {
+            let warning = Diagnostic::warning(&keyword, "Match has no wildcard arm");
+            self.warnings.push(warning);
+        }
+
+        Ok(Statement::MatchStmt(keyword, Box::new(value), arms))
+    }
+
+    fn match_arm(&mut self) -> Result<MatchArm, ()> {
+        let mut patterns = vec![self.pattern()?];
+        while self.curr_matches_type(TokenType::PIPE) {
+            let pipe = self.tokens[self.current].clone();
+            self.current += 1;
+            patterns.push(self.pattern()?);
+            // the name would be unset whenever another alternative matched
+            if patterns.iter().any(|p| matches!(p, Pattern::Binding(_))) {
+                report(&Diagnostic::syntax(&pipe, "Alternatives can't bind a name"));
+                return Err(());
+            }
+        }
+
+        let mut guard = None;
+        if self.curr_matches_type(TokenType::IF) {
+            self.current += 1;
+            guard = Some(Box::new(self.parse_assignment()?));
+        }
+
+        if !self.curr_matches_type(TokenType::FAT_ARROW) {
+            self.print_token_err("Expected '=>' after pattern")?;
+        }
+        self.current += 1;
+
+        let body = self.parse_arm_body()?;
+        Ok(MatchArm {
+            patterns,
+            guard,
+            body: Box::new(body),
+        })
+    }
+
+    /// The statement an arm runs. Unlike elsewhere, a `,` can end it in place of the `;`, and
+    /// the last arm's can be left out before the closing `}`.
+    fn parse_arm_body(&mut self) -> Result<Expr, ()> {
+        if self.curr_matches_type(TokenType::LEFT_BRACE) {
+            return self.parse_expression();
+        }
+
+        let body = self.parse_assignment()?;
+        if matches!(
+            body,
+            Expr::Stmt(Statement::IfStmt(_))
+                | Expr::Stmt(Statement::ForStmt(..))
+                | Expr::Stmt(Statement::WhileStmt(_))
+                | Expr::Stmt(Statement::MatchStmt(..))
+        ) {
+            return Ok(body);
+        }
+
+        match self.tokens[self.current].token_type {
+            TokenType::SEMICOLON | TokenType::COMMA => self.current += 1,
+            TokenType::RIGHT_BRACE => {}
+            _ => self.print_token_err("Expected ',' or ';' after match arm")?,
+        }
+
+        Ok(body)
+    }
+
+    /// A literal, `-` and a number, `_`, or a name to bind.
+    fn pattern(&mut self) -> Result<Pattern, ()> {
+        let token = self.tokens[self.current].clone();
+        let next = self.tokens.get(self.current + 1).cloned();
+        match token.token_type {
+            TokenType::IDENTIFIER if token.lexeme == "_" => {
+                self.current += 1;
+                Ok(Pattern::Wildcard(token))
+            }
+            TokenType::IDENTIFIER => {
+                self.current += 1;
+                Ok(Pattern::Binding(token))
+            }
+            TokenType::NUMBER
+            | TokenType::BIGINT
+            | TokenType::STRING
+            | TokenType::TRUE
+            | TokenType::FALSE
+            | TokenType::NIL => {
+                self.current += 1;
+                Ok(Pattern::Value(Box::new(Expr::Literal(token))))
+            }
+            TokenType::MINUS
+                if next.as_ref().is_some_and(|next| {
+                    matches!(next.token_type, TokenType::NUMBER | TokenType::BIGINT)
+                }) =>
+            {
+                self.current += 2;
+                let number = Box::new(Expr::Literal(next.unwrap()));
+                Ok(Pattern::Value(Box::new(Expr::Unary(token, number))))
+            }
+            _ => {
+                self.print_token_err("Expected a pattern")?;
+                Err(())
+            }
+        }
+    }
+
     fn handle_for_stmt(
         &mut self,
     ) -> Result<(Option<Box<Expr>>, Expr, Option<Box<Expr>>, Expr), ()> {
@@ -340,12 +852,60 @@ impl Parser {
         Ok((var_init, condition, var_update, expr))
     }
 
+    fn handle_import_stmt(&mut self) -> Result<(Token, Option<Vec<Token>>), ()> {
+        // import "path"; or import { a, b } from "path";
+        let mut names: Option<Vec<Token>> = None;
+        if self.curr_matches_type(TokenType::LEFT_BRACE) {
+            self.current += 1;
+            let mut imported: Vec<Token> = vec![];
+            loop {
+                if !self.curr_matches_type(TokenType::IDENTIFIER) {
+                    self.print_token_err("Expected name to import")?;
+                }
+
+                imported.push(self.tokens[self.current].clone());
+                self.current += 1;
+                if !self.curr_matches_type(TokenType::COMMA) {
+                    break;
+                }
+
+                self.current += 1;
+            }
+
+            if !self.curr_matches_type(TokenType::RIGHT_BRACE) {
+                self.print_token_err("Missing '}'")?;
+            }
+
+            self.current += 1;
+            if self.tokens[self.current].lexeme != "from" {
+                self.print_token_err("Expected 'from' after import list")?;
+            }
+
+            self.current += 1;
+            names = Some(imported);
+        }
+
+        if !self.curr_matches_type(TokenType::STRING) {
+            self.print_token_err("Expected module path string")?;
+        }
+
+        let path = self.tokens[self.current].clone();
+        self.current += 1;
+        Ok((path, names))
+    }
+
     fn variable_declaration(&mut self) -> Result<Expr, ()> {
         let variable = self.tokens[self.current].clone();
         let mut value = None;
-        self.current += 1;
         if variable.token_type != TokenType::IDENTIFIER {
-            return Err(());
+            self.print_token_err("Expected variable name")?;
+        }
+        self.current += 1;
+
+        let mut annotation = None;
+        if self.curr_matches_type(TokenType::COLON) {
+            self.current += 1;
+            annotation = Some(self.parse_type()?);
         }
 
         if self.tokens[self.current].token_type == TokenType::EQUAL {
@@ -355,17 +915,48 @@ impl Parser {
             value = Some(Box::new(expr));
         }
 
-        Ok(Expr::Stmt(Statement::DeclarationStmt(variable, value)))
+        Ok(Expr::Stmt(Statement::DeclarationStmt(
+            variable, annotation, value,
+        )))
+    }
+
+    fn parse_type(&mut self) -> Result<Token, ()> {
+        let name = self.tokens[self.current].clone();
+        if !matches!(name.token_type, TokenType::IDENTIFIER | TokenType::NIL) {
+            self.print_token_err("Expected type name")?;
+        }
+
+        if Type::from_name(&name.lexeme).is_none() {
+            self.print_token_err("Unknown type")?;
+        }
+
+        self.current += 1;
+        Ok(name)
+    }
+
+    fn nested(&mut self, parse_fn: fn(&mut Self) -> Result<Expr, ()>) -> Result<Expr, ()> {
+        if self.nesting >= MAX_NESTING {
+            self.print_token_err("Too much nesting")?;
+        }
+
+        self.nesting += 1;
+        let res = parse_fn(self);
+        self.nesting -= 1;
+
+        res
     }
 
     #[inline]
     fn empty_scope_check(&self, expr: &Expr) -> Result<(), ()> {
         match expr {
-            Expr::Scope(ref exprs) => {
+            Expr::Scope(_, ref exprs) => {
                 if exprs.len() == 0 {
                     self.print_token_err("Invalid condition for the loop")?;
                 }
             }
+            Expr::Map(_, ref entries) if entries.is_empty() => {
+                self.print_token_err("Invalid condition for the loop")?;
+            }
             _ => {}
         }
 
@@ -374,16 +965,76 @@ impl Parser {
 
     #[inline]
     fn print_token_err(&self, message: &str) -> Result<(), ()> {
-        eprintln!(
-            "[line {}] Error at '{}': {}.",
-            self.tokens[self.current].line_num, self.tokens[self.current].lexeme, message
-        );
+        report(&Diagnostic::syntax(&self.tokens[self.current], message));
 
         return Err(());
     }
 
+    /// Whether the `--` after `target` decrements it, rather than starting `- -operand`.
+    fn is_postfix_decrement(&self, target: &Expr) -> bool {
+        let next = self
+            .tokens
+            .get(self.current + 1)
+            .map(|token| token.token_type);
+        is_assignable(target)
+            && !matches!(
+                next,
+                Some(
+                    TokenType::NUMBER
+                        | TokenType::BIGINT
+                        | TokenType::STRING
+                        | TokenType::IDENTIFIER
+                        | TokenType::TRUE
+                        | TokenType::FALSE
+                        | TokenType::NIL
+                        | TokenType::LEFT_PAREN
+                )
+            )
+    }
+
+    /// Replaces the current `--` with two `-` tokens.
+    fn split_minus_minus(&mut self) {
+        let mut first = self.tokens[self.current].clone();
+        first.token_type = TokenType::MINUS;
+        first.lexeme = String::from("-");
+        let mut second = first.clone();
+        second.column += 1;
+        self.tokens[self.current] = first;
+        self.tokens.insert(self.current + 1, second);
+    }
+
+    /// Checks that `target` is a variable or map entry that `operator` can update.
+    fn assignable(&self, target: &Expr, operator: &Token) -> Result<(), ()> {
+        match is_assignable(target) {
+            true => Ok(()),
+            false => {
+                report(&Diagnostic::syntax(
+                    operator,
+                    "Cannot assign to non-identifier",
+                ));
+                Err(())
+            }
+        }
+    }
+
+    /// Reports a missing closing delimiter, pointing back at the one it should match.
+    fn print_unclosed_err(&self, message: &str, opener: &Token, label: &str) -> Result<(), ()> {
+        let diagnostic = Diagnostic::syntax(&self.tokens[self.current], message);
+        report(&diagnostic.with_label(opener, label));
+
+        Err(())
+    }
+
     #[inline]
     fn curr_matches_type(&self, t_type: TokenType) -> bool {
         self.tokens[self.current].token_type == t_type
     }
 }
+
+fn is_assignable(target: &Expr) -> bool {
+    match target {
+        Expr::Literal(token) => token.token_type == TokenType::IDENTIFIER,
+        Expr::Index(..) => true,
+        _ => false,
+    }
+}
@@ -1,7 +1,107 @@
 #[cfg(test)]
 mod tests {
+    use crate::parser::Parser;
+    use crate::tokenizer::tokenize;
+
     #[test]
-    fn test_fn() {
-        todo!();
+    fn binary_operators_follow_precedence() {
+        let (tokens, _) = tokenize(String::from("1 + 2 * 3 == 7 and !false"));
+        let expr = Parser::new(tokens).parse_assignment().unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "(and (== (+ 1.0 (* 2.0 3.0)) 7.0) (! false))"
+        );
+    }
+
+    #[test]
+    fn ternaries_bind_looser_than_or_and_group_to_the_right() {
+        let (tokens, _) = tokenize(String::from("a or b ? 1 : c ? 2 : 3"));
+        let expr = Parser::new(tokens).parse_assignment().unwrap();
+        assert_eq!(expr.to_string(), "(?: (or a b) 1.0 (?: c 2.0 3.0))");
+
+        let (tokens, _) = tokenize(String::from("print a ? 1;"));
+        assert!(Parser::new(tokens).parse().is_err());
+    }
+
+    #[test]
+    fn power_binds_tighter_than_unary_minus() {
+        let (tokens, _) = tokenize(String::from("-2 ** 3 ** 2 % 4 div ++i ** 2"));
+        let expr = Parser::new(tokens).parse_assignment().unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "(div (% (- (** 2.0 (** 3.0 2.0))) 4.0) (** (++ i) 2.0))"
+        );
+    }
+
+    #[test]
+    fn bitwise_operators_sit_between_comparison_and_additive() {
+        let (tokens, _) = tokenize(String::from("a | b ^ c & d << 1 + 2 < ~e +% f *% g"));
+        let expr = Parser::new(tokens).parse_assignment().unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "(< (| a (^ b (& c (<< d (+ 1.0 2.0))))) (+% (~ e) (*% f g)))"
+        );
+    }
+
+    #[test]
+    fn map_keys_can_be_any_expression() {
+        let source = "{-1: 2, \"a\" + \"b\": c ? 1 : 2, (d): {}}";
+        let (tokens, _) = tokenize(String::from(source));
+        let expr = Parser::new(tokens).parse_assignment().unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "{(- 1.0): 2.0, (+ a b): (?: c 1.0 2.0), (group d): {}}"
+        );
+    }
+
+    #[test]
+    fn compound_assignments_and_increments_need_a_target() {
+        let (tokens, _) = tokenize(String::from("a += b++ * --m[\"k\"]"));
+        let expr = Parser::new(tokens).parse_assignment().unwrap();
+        assert_eq!(expr.to_string(), "assign a += (* (b ++) (-- (index m k)))");
+
+        for source in ["1 += 2;", "(a)++;", "++\"s\";"] {
+            let (tokens, _) = tokenize(String::from(source));
+            assert!(Parser::new(tokens).parse().is_err(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn minus_minus_without_a_target_negates_twice() {
+        for (source, expected) in [
+            ("--(3)", "(- (- (group 3.0)))"),
+            ("----1", "(- (- (- (- 1.0))))"),
+            ("--f()", "(- (- (call f)))"),
+            ("2--1 * 3", "(- 2.0 (* (- 1.0) 3.0))"),
+            ("x--1", "(- x (- 1.0))"),
+            ("--x - x--", "(- (-- x) (x --))"),
+        ] {
+            let (tokens, _) = tokenize(String::from(source));
+            let expr = Parser::new(tokens).parse_assignment().unwrap();
+            assert_eq!(expr.to_string(), expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn match_arms_take_alternatives_guards_and_bindings() {
+        let source = "match (x) { 1 | -2 => print 1, n if n > 3 => { print n; } _ => print 0 }";
+        let (tokens, _) = tokenize(String::from(source));
+        let program = Parser::new(tokens).parse().unwrap();
+        assert_eq!(
+            program[0].to_string(),
+            "match (group x)\ncase 1.0 | (- 2.0)\nprint 1.0\ncase bind n if (> n 3.0)\nscoped \n{\nprint n\n}\ncase _\nprint 0.0\nend match"
+        );
+
+        for source in [
+            "match x { _ => print 1; }",
+            "match (x) { 1 print 1; }",
+            "match (x) { a | 1 => print 1; }",
+            "match (x) { 1 + 1 => print 1; }",
+            "match (x) { _ => print 1 print 2 }",
+            "match (x) { _ => print 1;",
+        ] {
+            let (tokens, _) = tokenize(String::from(source));
+            assert!(Parser::new(tokens).parse().is_err(), "{}", source);
+        }
     }
 }
@@ -0,0 +1,158 @@
+use crate::runner::{Hook, State};
+use crate::Expr;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+mod tests;
+
+/// Frame at the bottom of every folded stack.
+const ROOT_FRAME: &str = "main";
+
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct LineStats {
+    pub hits: u64,
+    /// Time spent in statements starting on the line, including the statements nested in them.
+    pub time: Duration,
+}
+
+/// What a profiled run spent its time on.
+#[derive(Default, Clone)]
+pub struct Profile {
+    lines: BTreeMap<u32, LineStats>,
+    /// Self time per stack of blocks, keyed by the `;` joined frames.
+    stacks: BTreeMap<String, Duration>,
+}
+
+impl Profile {
+    pub fn line(&self, line: u32) -> Option<LineStats> {
+        self.lines.get(&line).copied()
+    }
+
+    /// A table of hits and time per source line, in line order.
+    pub fn report(&self, source: &str) -> String {
+        let lines: Vec<&str> = source.lines().collect();
+        let mut report = format!("{:>6} {:>10} {:>12}  source\n", "line", "hits", "time (ms)");
+        for (line, stats) in self.lines.iter() {
+            let code = (*line as usize)
+                .checked_sub(1)
+                .and_then(|index| lines.get(index))
+                .map_or("", |code| code.trim());
+            let _ = writeln!(
+                report,
+                "{:>6} {:>10} {:>12.3}  {}",
+                line,
+                stats.hits,
+                stats.time.as_secs_f64() * 1000.0,
+                code
+            );
+        }
+
+        report
+    }
+
+    /// Folded stacks as read by flamegraph tools: one `frame;frame microseconds` line per stack.
+    /// Lox has no functions yet, so every block is a frame, named after the line it starts on.
+    pub fn folded(&self) -> String {
+        let mut folded = String::new();
+        for (stack, time) in self.stacks.iter() {
+            let _ = writeln!(folded, "{} {}", stack, time.as_micros());
+        }
+
+        folded
+    }
+}
+
+struct Active {
+    line: u32,
+    start: Instant,
+    block: bool,
+}
+
+/// Records a `Profile` as statements run.
+pub struct Profiler {
+    profile: Rc<RefCell<Profile>>,
+    active: Vec<Active>,
+    frames: Vec<String>,
+    /// When time was last attributed to a stack; `None` between runs.
+    last: Option<Instant>,
+}
+
+impl Profiler {
+    pub fn new(profile: Rc<RefCell<Profile>>) -> Self {
+        Profiler {
+            profile,
+            active: vec![],
+            frames: vec![ROOT_FRAME.to_string()],
+            last: None,
+        }
+    }
+
+    /// Charges the time since the last change of stack to the current stack.
+    fn attribute(&mut self, now: Instant) {
+        if let Some(last) = self.last {
+            let mut profile = self.profile.borrow_mut();
+            *profile.stacks.entry(self.frames.join(";")).or_default() += now - last;
+        }
+
+        self.last = Some(now);
+    }
+}
+
+impl Hook for Profiler {
+    fn before_statement(&mut self, _: &mut State, expr: &Expr, _: usize) -> Result<(), ()> {
+        let now = Instant::now();
+        let line = expr.line();
+        let block = matches!(expr, Expr::Scope(..));
+        if block {
+            self.attribute(now);
+            self.frames.push(format!("block@{}", line));
+        } else {
+            self.profile
+                .borrow_mut()
+                .lines
+                .entry(line)
+                .or_default()
+                .hits += 1;
+        }
+
+        if self.last.is_none() {
+            self.last = Some(now);
+        }
+        self.active.push(Active {
+            line,
+            start: now,
+            block,
+        });
+
+        Ok(())
+    }
+
+    fn after_statement(&mut self, depth: usize) {
+        let now = Instant::now();
+        let Some(statement) = self.active.pop() else {
+            return;
+        };
+
+        // a statement nested on the same line is already counted in its parent's time
+        let nested = self
+            .active
+            .iter()
+            .any(|outer| !outer.block && outer.line == statement.line);
+        if !statement.block && !nested {
+            let mut profile = self.profile.borrow_mut();
+            profile.lines.entry(statement.line).or_default().time += now - statement.start;
+        }
+
+        if statement.block {
+            self.attribute(now);
+            self.frames.pop();
+        }
+
+        if depth == 1 {
+            self.attribute(now);
+            self.last = None;
+        }
+    }
+}
@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod tests {
+    use crate::{Interpreter, SharedBuffer};
+
+    fn profile(source: &str) -> crate::Profile {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(SharedBuffer::new());
+        interpreter.enable_profiling();
+        interpreter.run_source(source).unwrap();
+        interpreter.profile().unwrap()
+    }
+
+    #[test]
+    fn counts_hits_per_line() {
+        let profile = profile("var i = 0;\nwhile (i < 3) {\n  i = i + 1;\n}\nprint i;");
+        assert_eq!(profile.line(1).unwrap().hits, 1);
+        assert_eq!(profile.line(2).unwrap().hits, 1);
+        assert_eq!(profile.line(3).unwrap().hits, 3);
+        assert_eq!(profile.line(4), None);
+
+        let report = profile.report("var i = 0;\nwhile (i < 3) {\n  i = i + 1;\n}\nprint i;");
+        let rows: Vec<_> = report.lines().skip(1).map(|row| row.trim_start()).collect();
+        assert_eq!(rows.len(), 4);
+        assert!(rows[2].starts_with("3          3 "));
+        assert!(rows[2].ends_with("  i = i + 1;"));
+    }
+
+    #[test]
+    fn folds_stacks_per_block() {
+        let profile = profile("print 1;\n{\n  {\n    print 2;\n  }\n}");
+        let stacks: Vec<_> = profile
+            .folded()
+            .lines()
+            .map(|line| line.rsplit_once(' ').unwrap().0.to_string())
+            .collect();
+        assert_eq!(stacks, ["main", "main;block@2", "main;block@2;block@3"]);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::output::errln;
+use std::time::{Duration, Instant};
+
+/// Deepest evaluation nesting allowed unless configured otherwise. Every operand of a flat chain
+/// like `1 + 1 + 1` nests one level deeper, so this is as high as the command line's interpreter
+/// thread can safely hold in debug builds, which is about 5000. Runaway recursion then becomes a
+/// Lox error rather than an abort.
+pub const DEFAULT_MAX_DEPTH: usize = 4000;
+
+/// Execution budgets for running untrusted scripts.
+#[derive(Debug, Clone)]
+pub struct Limits {
+    pub max_steps: Option<u64>,
+    pub max_depth: usize,
+    pub timeout: Option<Duration>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_steps: None,
+            max_depth: DEFAULT_MAX_DEPTH,
+            timeout: None,
+        }
+    }
+}
+
+/// Tracks usage against `Limits` for the current run.
+#[derive(Default)]
+pub struct Budget {
+    pub limits: Limits,
+    steps: u64,
+    depth: usize,
+    deadline: Option<Instant>,
+    exceeded: bool,
+}
+
+impl Budget {
+    /// Resets the counters and starts the clock for a new run.
+    pub fn start(&mut self) {
+        self.steps = 0;
+        self.depth = 0;
+        self.exceeded = false;
+        self.deadline = self.limits.timeout.map(|timeout| Instant::now() + timeout);
+    }
+
+    /// Whether the last failure was caused by a limit rather than a regular runtime error.
+    pub fn exceeded(&self) -> bool {
+        self.exceeded
+    }
+
+    /// Charges one executed statement.
+    pub fn step(&mut self) -> Result<(), ()> {
+        self.steps += 1;
+        if let Some(max_steps) = self.limits.max_steps {
+            if self.steps > max_steps {
+                errln!("Step limit exceeded: more than {} statements.", max_steps);
+                return self.fail();
+            }
+        }
+
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                let timeout = self.limits.timeout.unwrap_or_default();
+                errln!("Timed out after {} ms.", timeout.as_millis());
+                return self.fail();
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn enter(&mut self) -> Result<(), ()> {
+        if self.depth >= self.limits.max_depth {
+            errln!(
+                "Maximum nesting depth of {} exceeded.",
+                self.limits.max_depth
+            );
+            return self.fail();
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub fn exit(&mut self) {
+        self.depth -= 1;
+    }
+
+    #[inline]
+    fn fail(&mut self) -> Result<(), ()> {
+        self.exceeded = true;
+        Err(())
+    }
+}
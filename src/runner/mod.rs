@@ -1,11 +1,51 @@
-use crate::evaluate::Eval;
-use codecrafters_interpreter::{Expr, Statement, Token, TokenType};
+use crate::builtins::{self, Capabilities, Random};
+use crate::diagnostics::{report, Diagnostic, Label};
+use crate::evaluate::{number_literal, Eval};
+use crate::interpreter::{HostFunction, Value};
+use crate::output::{errln, outln};
+use crate::{Expr, MatchArm, Pattern, Statement, Token, TokenType};
 use std::collections::HashMap;
+use std::fs;
+use std::mem;
+use std::path::PathBuf;
+mod limits;
+mod modules;
 mod tests;
 
+use limits::Budget;
+pub use limits::Limits;
+
 pub struct State {
     scopes: Vec<Scope>,
     len: usize,
+    random: Random,
+    capabilities: Capabilities,
+    import_stack: Vec<PathBuf>,
+    modules: HashMap<PathBuf, HashMap<String, Token>>,
+    exports: Vec<String>,
+    host_functions: HashMap<String, HostFunction>,
+    budget: Budget,
+    hooks: Vec<Box<dyn Hook>>,
+    depth: usize,
+}
+
+/// Observes statement execution, e.g. the debugger pausing before each statement. Statements of
+/// imported modules are not observed.
+pub trait Hook {
+    /// Called before running a statement; `depth` is how many statements enclose it. An `Err` stops
+    /// the program.
+    fn before_statement(&mut self, state: &mut State, expr: &Expr, depth: usize) -> Result<(), ()>;
+
+    /// Called once the statement last passed to `before_statement` has finished, even if it failed.
+    fn after_statement(&mut self, _depth: usize) {}
+
+    /// Called each time an `if` or loop condition is evaluated, with whether it held.
+    fn branch(&mut self, _condition: &Expr, _taken: bool) {}
+
+    /// Whether the hook deliberately stopped the program, as opposed to a runtime error.
+    fn stopped(&self) -> bool {
+        false
+    }
 }
 
 struct Scope {
@@ -33,9 +73,95 @@ impl State {
         State {
             scopes: vec![Scope::new()],
             len: 0,
+            random: Random::from_time(),
+            capabilities: Capabilities::new(),
+            import_stack: vec![],
+            modules: HashMap::new(),
+            exports: vec![],
+            host_functions: HashMap::new(),
+            budget: Budget::default(),
+            hooks: vec![],
+            depth: 0,
         }
     }
 
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.budget.limits = limits;
+    }
+
+    /// Resets the execution budget; called at the start of every top level run.
+    pub fn start_budget(&mut self) {
+        self.budget.start();
+    }
+
+    pub fn limit_exceeded(&self) -> bool {
+        self.budget.exceeded()
+    }
+
+    pub fn register_host_fn(&mut self, name: &str, function: HostFunction) {
+        self.host_functions.insert(name.to_string(), function);
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<&Token> {
+        self.scopes[0].variables.get(name)
+    }
+
+    pub fn set_global(&mut self, name: &str, value: Token) {
+        self.insert_var(name.to_string(), value, 0);
+    }
+
+    /// Drops any block scopes left open by a runtime error so the state can be reused.
+    pub fn recover(&mut self) {
+        self.scopes.truncate(1);
+        self.len = 0;
+    }
+
+    /// Records the file being run, which imports are resolved against.
+    pub fn set_file(&mut self, filename: &str) {
+        if let Ok(path) = fs::canonicalize(filename) {
+            self.import_stack = vec![path];
+        }
+    }
+
+    /// The script's path as error messages show it, once `set_file` has been called.
+    pub fn file_name(&self) -> Option<String> {
+        self.import_stack
+            .first()
+            .map(|path| modules::display_path(path))
+    }
+
+    pub fn allow_read(&mut self, dir: &str) -> Result<(), ()> {
+        self.capabilities.allow_read(dir)
+    }
+
+    pub fn allow_write(&mut self, dir: &str) -> Result<(), ()> {
+        self.capabilities.allow_write(dir)
+    }
+
+    pub fn add_hook(&mut self, hook: Box<dyn Hook>) {
+        self.hooks.push(hook);
+    }
+
+    pub fn take_hooks(&mut self) -> Vec<Box<dyn Hook>> {
+        mem::take(&mut self.hooks)
+    }
+
+    /// The variables of every open scope, outermost first, each sorted by name.
+    pub fn scope_variables(&self) -> Vec<Vec<(&String, &Token)>> {
+        self.scopes
+            .iter()
+            .map(|scope| {
+                let mut variables: Vec<_> = scope.variables.iter().collect();
+                variables.sort_by(|a, b| a.0.cmp(b.0));
+                variables
+            })
+            .collect()
+    }
+
+    pub fn seed_random(&mut self, seed: u64) {
+        self.random = Random::new(seed);
+    }
+
     pub fn run(&mut self, expressions: Vec<Expr>) -> Result<(), ()> {
         for expr in expressions {
             self.run_expression(expr)?;
@@ -45,33 +171,115 @@ impl State {
     }
 
     fn run_expression(&mut self, expr: Expr) -> Result<(), ()> {
+        self.budget.step()?;
+        self.budget.enter()?;
+        self.depth += 1;
+        let observed = self.observed();
+        let res = match observed {
+            true => self
+                .before_statement(&expr)
+                .and_then(|_| self.run_statement(expr)),
+            false => self.run_statement(expr),
+        };
+        if observed {
+            self.after_statement();
+        }
+        self.depth -= 1;
+        self.budget.exit();
+
+        res
+    }
+
+    fn observed(&self) -> bool {
+        !self.hooks.is_empty() && self.import_stack.len() <= 1
+    }
+
+    fn before_statement(&mut self, expr: &Expr) -> Result<(), ()> {
+        let mut hooks = mem::take(&mut self.hooks);
+        // every hook sees the statement, so each gets its matching `after_statement`
+        let mut res = Ok(());
+        for hook in hooks.iter_mut() {
+            if hook.before_statement(self, expr, self.depth).is_err() {
+                res = Err(());
+            }
+        }
+        self.hooks = hooks;
+
+        res
+    }
+
+    fn after_statement(&mut self) {
+        for hook in self.hooks.iter_mut() {
+            hook.after_statement(self.depth);
+        }
+    }
+
+    /// Evaluates an `if` or loop condition, telling any hooks which way it went.
+    fn condition(&mut self, condition: &Expr) -> Result<bool, ()> {
+        let token = self.evaluate(condition.clone())?;
+        let taken = Self::get_bool(token)?;
+        if self.observed() {
+            for hook in self.hooks.iter_mut() {
+                hook.branch(condition, taken);
+            }
+        }
+
+        Ok(taken)
+    }
+
+    fn run_statement(&mut self, expr: Expr) -> Result<(), ()> {
         match expr {
             Expr::Stmt(statement) => match statement {
                 Statement::PrintStmt(expr) => {
                     let output = self.evaluate(*expr)?;
-                    if output.token_type == TokenType::STRING {
-                        println!("{}", output.literal);
-                    } else {
-                        println!("{}", output.lexeme);
-                    }
+                    outln!("{}", output.value_string());
                 }
-                Statement::DeclarationStmt(variable, value) => self.declare(variable, value)?,
+                Statement::DeclarationStmt(variable, _, value) => self.declare(variable, value)?,
                 Statement::AssignmentStmt(variable, value) => {
                     let _ = self.assign(variable, value)?;
                     return Ok(());
                 }
+                statement @ (Statement::IndexAssignmentStmt(..)
+                | Statement::CompoundAssignmentStmt(..)
+                | Statement::IncrementStmt(..)) => {
+                    self.evaluate(Expr::Stmt(statement))?;
+                }
+                Statement::ImportStmt(path, names) => self.import(path, names)?,
+                Statement::ExportStmt(declaration) => {
+                    if self.len != 0 {
+                        errln!("Only top level declarations can be exported.");
+                        return Err(());
+                    }
+
+                    if let Expr::Stmt(Statement::DeclarationStmt(ref variable, ..)) = *declaration {
+                        self.exports.push(variable.lexeme.clone());
+                    }
+                    self.run_expression(*declaration)?;
+                }
                 Statement::IfStmt(conditionals) => {
                     for conditional in conditionals {
-                        let condition = self.evaluate(*conditional.0)?;
-                        if Self::get_bool(condition)? {
+                        // the parser gives `else` a literal `true` rather than a condition
+                        let is_else = !matches!(*conditional.0, Expr::Grouping(_));
+                        if is_else || self.condition(&conditional.0)? {
                             self.run_expression(*conditional.1)?;
                             break;
                         }
                     }
                 }
+                Statement::MatchStmt(_, value, arms) => {
+                    let value = self.evaluate(*value)?;
+                    for arm in arms {
+                        // a binding lives in its own scope, covering the guard and the body
+                        self.add_scope();
+                        let res = self.run_arm(&value, arm);
+                        self.remove_scope();
+                        if res? {
+                            break;
+                        }
+                    }
+                }
                 Statement::WhileStmt(conditional) => loop {
-                    let condition = self.evaluate(*conditional.0.clone())?;
-                    if !Self::get_bool(condition)? {
+                    if !self.condition(&conditional.0)? {
                         break;
                     }
 
@@ -84,8 +292,7 @@ impl State {
                     }
 
                     loop {
-                        let token = self.evaluate(*condition.clone())?;
-                        if !Self::get_bool(token)? {
+                        if !self.condition(&condition)? {
                             break;
                         }
 
@@ -98,7 +305,7 @@ impl State {
                     self.remove_scope();
                 }
             },
-            Expr::Scope(exprs) => {
+            Expr::Scope(_, exprs) => {
                 self.add_scope();
                 self.run(exprs)?;
                 self.remove_scope();
@@ -111,6 +318,40 @@ impl State {
         Ok(())
     }
 
+    /// Runs the arm's body if `value` matches one of its patterns and the guard holds.
+    /// Returns whether it ran.
+    fn run_arm(&mut self, value: &Token, arm: MatchArm) -> Result<bool, ()> {
+        let mut matched = false;
+        for pattern in arm.patterns {
+            matched = match pattern {
+                Pattern::Value(expected) => {
+                    let expected = self.evaluate(*expected)?;
+                    Self::values_equal(value, &expected)
+                }
+                Pattern::Wildcard(_) => true,
+                Pattern::Binding(name) => {
+                    self.insert_var(name.lexeme, value.clone(), self.len);
+                    true
+                }
+            };
+            if matched {
+                break;
+            }
+        }
+
+        if !matched {
+            return Ok(false);
+        }
+        if let Some(guard) = arm.guard {
+            if !self.condition(&guard)? {
+                return Ok(false);
+            }
+        }
+
+        self.run_expression(*arm.body)?;
+        Ok(true)
+    }
+
     fn declare(&mut self, variable: Token, value: Option<Box<Expr>>) -> Result<(), ()> {
         match value {
             Some(expr) => {
@@ -136,31 +377,54 @@ impl State {
     }
 
     fn assign(&mut self, variable: Token, value: Box<Expr>) -> Result<Token, ()> {
-        let scope = self.has_var(&variable.lexeme);
-        if scope == -1 {
-            eprintln!(
-                "[line {}] Undeclared variable: '{}'",
-                variable.line_num, variable.lexeme
-            );
+        let Some(scope) = self.has_var(&variable.lexeme) else {
+            report(&undeclared(&variable));
             return Err(());
-        }
+        };
 
         let token = self.evaluate(*value)?;
-        self.insert_var(variable.lexeme, token.clone(), scope as usize);
+        self.insert_var(variable.lexeme, token.clone(), scope);
 
         Ok(token)
     }
 
-    fn has_var(&self, name: &str) -> i8 {
-        let mut scope: i8 = -1;
-        for s in (0..(self.len + 1)).rev() {
-            if self.scopes[s].has_var(name) {
-                scope = s as i8;
-                break;
+    /// Applies a compound assignment or increment to a variable or map entry. Returns the stored
+    /// value, or the previous one for a postfix increment.
+    fn update(
+        &mut self,
+        target: Expr,
+        operator: Token,
+        value: Option<Expr>,
+        postfix: bool,
+    ) -> Result<Token, ()> {
+        let variable = match target {
+            Expr::Literal(variable) => variable,
+            Expr::Index(target, bracket, key) => {
+                return self.eval_index_update(*target, bracket, *key, operator, value, postfix);
             }
-        }
+            _ => {
+                errln!("Unexpected/invalid statement.");
+                return Err(());
+            }
+        };
 
-        scope
+        let Some(scope) = self.has_var(&variable.lexeme) else {
+            report(&undeclared(&variable));
+            return Err(());
+        };
+
+        let old = self.get_var(&variable.lexeme, scope);
+        let new = self.eval_update(old.clone(), &operator, value)?;
+        self.insert_var(variable.lexeme, new.clone(), scope);
+
+        Ok(if postfix { old } else { new })
+    }
+
+    /// The innermost scope declaring `name`.
+    fn has_var(&self, name: &str) -> Option<usize> {
+        (0..(self.len + 1))
+            .rev()
+            .find(|&scope| self.scopes[scope].has_var(name))
     }
 
     fn get_var(&mut self, name: &str, scope: usize) -> Token {
@@ -189,25 +453,54 @@ impl State {
 
 impl Eval for State {
     fn evaluate(&mut self, expr: Expr) -> Result<Token, ()> {
+        self.budget.enter()?;
+        let res = self.eval_expr(expr);
+        self.budget.exit();
+
+        res
+    }
+
+    fn call_builtin(&mut self, callee: &Token, args: Vec<Token>) -> Option<Result<Token, ()>> {
+        if let Some(function) = self.host_functions.get_mut(&callee.lexeme) {
+            let values: Vec<Value> = args.iter().map(Value::from_token).collect();
+            return match function(&values) {
+                Ok(value) => Some(Ok(value.to_token(callee.line_num))),
+                Err(message) => {
+                    report(&Diagnostic::runtime(callee, &message));
+                    Some(Err(()))
+                }
+            };
+        }
+
+        match callee.lexeme.as_str() {
+            "random" => Some(self.random.next_number(callee, args)),
+            _ => match self.capabilities.call(callee, &args) {
+                Some(res) => Some(res),
+                None => builtins::call(callee, args),
+            },
+        }
+    }
+}
+
+impl State {
+    fn eval_expr(&mut self, expr: Expr) -> Result<Token, ()> {
         let res: Token;
         match expr {
             Expr::Literal(token) => match token.token_type {
-                TokenType::STRING
-                | TokenType::NUMBER
-                | TokenType::FALSE
-                | TokenType::TRUE
-                | TokenType::NIL => res = token,
+                TokenType::NUMBER | TokenType::BIGINT => res = number_literal(token),
+                TokenType::STRING | TokenType::FALSE | TokenType::TRUE | TokenType::NIL => {
+                    res = token
+                }
                 TokenType::IDENTIFIER => {
-                    let scope = self.has_var(&token.lexeme);
-                    if scope == -1 {
-                        eprintln!(
-                            "[line {}] Undeclared variable: '{}'",
-                            token.line_num, token.lexeme
-                        );
+                    let Some(scope) = self.has_var(&token.lexeme) else {
+                        if let Some(constant) = builtins::constant(&token.lexeme, token.line_num) {
+                            return Ok(constant);
+                        }
+
+                        report(&undeclared(&token));
                         return Err(());
-                    } else {
-                        res = self.get_var(&token.lexeme, scope as usize);
-                    }
+                    };
+                    res = self.get_var(&token.lexeme, scope);
                 }
                 _ => panic!("this shouldn't happen"),
             },
@@ -221,11 +514,28 @@ impl Eval for State {
                 TokenType::AND => res = Self::eval_logical_and_expr(self, *left_expr, *right_expr)?,
                 _ => panic!("this shouldn't happen"),
             },
+            Expr::Ternary(condition, _, then, otherwise) => {
+                res = self.eval_ternary_expr(*condition, *then, *otherwise)?;
+            }
             Expr::Stmt(Statement::AssignmentStmt(variable, value)) => {
                 res = self.assign(variable, value)?;
             }
+            Expr::Stmt(Statement::IndexAssignmentStmt(target, bracket, key, value)) => {
+                res = self.eval_index_assignment(*target, bracket, *key, *value)?;
+            }
+            Expr::Stmt(Statement::CompoundAssignmentStmt(target, operator, value)) => {
+                res = self.update(*target, operator, Some(*value), false)?;
+            }
+            Expr::Stmt(Statement::IncrementStmt(target, operator, postfix)) => {
+                res = self.update(*target, operator, None, postfix)?;
+            }
+            Expr::Map(brace, entries) => res = self.eval_map_expr(brace, entries)?,
+            Expr::Index(target, bracket, key) => {
+                res = self.eval_index_expr(*target, bracket, *key)?;
+            }
+            Expr::Call(callee, args) => res = self.eval_call_expr(callee, args)?,
             _ => {
-                eprintln!("Unexpected/invalid statement.");
+                errln!("Unexpected/invalid statement.");
                 return Err(());
             }
         }
@@ -233,3 +543,12 @@ impl Eval for State {
         Ok(res)
     }
 }
+
+fn undeclared(variable: &Token) -> Diagnostic {
+    let short = format!(
+        "[line {}] Undeclared variable: '{}'",
+        variable.line_num, variable.lexeme
+    );
+    let message = format!("Undeclared variable '{}'", variable.lexeme);
+    Diagnostic::new(short, &message, Label::new(variable, ""))
+}
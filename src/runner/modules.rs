@@ -0,0 +1,160 @@
+use super::{Scope, State};
+use crate::diagnostics::{report, use_source};
+use crate::output::errln;
+use crate::parser::Parser;
+use crate::tokenizer;
+use crate::Token;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs, mem};
+
+impl State {
+    pub(super) fn import(&mut self, path: Token, names: Option<Vec<Token>>) -> Result<(), ()> {
+        if self.len != 0 {
+            errln!(
+                "Imports are only allowed at the top level of a module.\n[line {}]",
+                path.line_num
+            );
+            return Err(());
+        }
+
+        let module_path = self.resolve_module(&path)?;
+        let exports = match self.modules.get(&module_path) {
+            Some(exports) => exports.clone(),
+            None => self.load_module(&path, module_path)?,
+        };
+
+        match names {
+            Some(names) => {
+                for name in names {
+                    match exports.get(&name.lexeme) {
+                        Some(value) => self.insert_var(name.lexeme, value.clone(), 0),
+                        None => {
+                            errln!(
+                                "Module '{}' has no export '{}'.\n[line {}]",
+                                path.literal,
+                                name.lexeme,
+                                name.line_num
+                            );
+                            return Err(());
+                        }
+                    }
+                }
+            }
+            None => {
+                for (name, value) in exports {
+                    self.insert_var(name, value, 0);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Module paths are relative to the file doing the import.
+    fn resolve_module(&self, path: &Token) -> Result<PathBuf, ()> {
+        let base = match self.import_stack.last().and_then(|file| file.parent()) {
+            Some(dir) => dir.to_path_buf(),
+            None => PathBuf::from("."),
+        };
+
+        match fs::canonicalize(base.join(&path.literal)) {
+            Ok(module_path) => Ok(module_path),
+            Err(_) => {
+                errln!(
+                    "Cannot find module '{}'.\n[line {}]",
+                    path.literal,
+                    path.line_num
+                );
+                Err(())
+            }
+        }
+    }
+
+    fn load_module(
+        &mut self,
+        path: &Token,
+        module_path: PathBuf,
+    ) -> Result<HashMap<String, Token>, ()> {
+        if let Some(pos) = self.import_stack.iter().position(|f| *f == module_path) {
+            let mut chain: Vec<String> = self.import_stack[pos..]
+                .iter()
+                .map(|file| display_path(file))
+                .collect();
+            chain.push(display_path(&module_path));
+            errln!(
+                "Import cycle detected: {}.\n[line {}]",
+                chain.join(" -> "),
+                path.line_num
+            );
+            return Err(());
+        }
+
+        let Ok(file_contents) = fs::read_to_string(&module_path) else {
+            errln!(
+                "Cannot read module '{}'.\n[line {}]",
+                path.literal,
+                path.line_num
+            );
+            return Err(());
+        };
+
+        // errors inside the module quote its own source
+        let _source = use_source(Some(display_path(&module_path)), &file_contents);
+        let (tokens, status_code) = tokenizer::tokenize(file_contents.clone());
+        let mut parser = Parser::new(tokens);
+        let parsed = match status_code {
+            0 => parser.parse(),
+            _ => Err(()),
+        };
+        let Ok(expressions) = parsed else {
+            Self::module_err(path, &module_path);
+            return Err(());
+        };
+        for warning in parser.warnings() {
+            report(warning);
+        }
+
+        // every module runs once, against its own top level scope
+        let scopes = mem::replace(&mut self.scopes, vec![Scope::new()]);
+        let len = mem::replace(&mut self.len, 0);
+        let outer_exports = mem::take(&mut self.exports);
+        self.import_stack.push(module_path.clone());
+        let res = self.run(expressions);
+        self.import_stack.pop();
+        let names = mem::replace(&mut self.exports, outer_exports);
+        let module_scopes = mem::replace(&mut self.scopes, scopes);
+        self.len = len;
+        if res.is_err() {
+            Self::module_err(path, &module_path);
+            return Err(());
+        }
+
+        let mut exports: HashMap<String, Token> = HashMap::new();
+        for name in names {
+            if let Some(value) = module_scopes[0].variables.get(&name) {
+                exports.insert(name, value.clone());
+            }
+        }
+
+        self.modules.insert(module_path, exports.clone());
+        Ok(exports)
+    }
+
+    #[inline]
+    fn module_err(path: &Token, module_path: &Path) {
+        errln!(
+            "Error in module '{}'.\n[line {}]",
+            display_path(module_path),
+            path.line_num
+        );
+    }
+}
+
+pub(super) fn display_path(path: &Path) -> String {
+    let relative = env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf));
+
+    relative.unwrap_or(path.to_path_buf()).display().to_string()
+}
@@ -1,7 +1,90 @@
 #[cfg(test)]
 mod tests {
+    use crate::parser::Parser;
+    use crate::runner::State;
+    use crate::tokenizer::tokenize;
+    use std::fs;
+    use std::path::PathBuf;
+
     #[test]
-    fn test_fn() {
-        todo!();
+    fn loops_update_variables_in_enclosing_scopes() {
+        let source = "var total = 0; for (var i = 1; i <= 4; i = i + 1) { total = total + i; }";
+        let (tokens, _) = tokenize(String::from(source));
+        let expressions = Parser::new(tokens).parse().unwrap();
+        let mut state = State::new();
+
+        assert!(state.run(expressions).is_ok());
+        assert_eq!(state.scopes[0].variables["total"].lexeme, "10");
+        assert!(!state.scopes[0].has_var("i"));
+    }
+
+    #[test]
+    fn match_runs_the_first_arm_that_matches() {
+        let source = "var log = \"\";
+            for (var i = 0; i < 6; i++) {
+                match (i) {
+                    0 => log += \"zero \",
+                    1 | 2 => log += \"small \",
+                    n if n > 3 => log += \"big \",
+                    n => log += \"other \",
+                }
+            }";
+        let (tokens, _) = tokenize(String::from(source));
+        let expressions = Parser::new(tokens).parse().unwrap();
+        let mut state = State::new();
+
+        assert!(state.run(expressions).is_ok());
+        assert_eq!(
+            state.scopes[0].variables["log"].literal,
+            "zero small small other big big "
+        );
+        assert!(!state.scopes[0].has_var("n"));
+    }
+
+    fn run_file(path: &PathBuf) -> (State, Result<(), ()>) {
+        let (tokens, _) = tokenize(fs::read_to_string(path).unwrap());
+        let expressions = Parser::new(tokens).parse().unwrap();
+        let mut state = State::new();
+        state.set_file(path.to_str().unwrap());
+        let res = state.run(expressions);
+
+        (state, res)
+    }
+
+    fn module_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("lox-{}-{}", name, std::process::id()));
+        fs::create_dir_all(dir.join("lib")).unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn import_binds_only_exported_globals() {
+        let dir = module_dir("import");
+        fs::write(
+            dir.join("lib/util.lox"),
+            "var hidden = 1; export var shown = hidden + 1;",
+        )
+        .unwrap();
+        fs::write(dir.join("main.lox"), "import \"lib/util.lox\";").unwrap();
+
+        let (state, res) = run_file(&dir.join("main.lox"));
+        assert!(res.is_ok());
+        assert_eq!(state.scopes[0].variables["shown"].lexeme, "2");
+        assert!(!state.scopes[0].has_var("hidden"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn import_cycles_are_errors() {
+        let dir = module_dir("cycle");
+        fs::write(dir.join("a.lox"), "import \"lib/b.lox\";").unwrap();
+        fs::write(dir.join("lib/b.lox"), "import \"../a.lox\";").unwrap();
+
+        let (_, res) = run_file(&dir.join("a.lox"));
+        assert!(res.is_err());
+
+        fs::remove_dir_all(dir).unwrap();
     }
 }
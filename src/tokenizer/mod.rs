@@ -1,26 +1,82 @@
-use codecrafters_interpreter::{Token, TokenType};
+use crate::diagnostics::{report, Diagnostic, Label};
+use crate::{BigInt, Token, TokenType};
+use std::str;
 mod tests;
+mod trivia;
+
+pub use trivia::{tokenize_lossless, tokens_json, LosslessToken, Trivia, TriviaKind};
+
+/// The source's characters, remembering where the last one read sits on its line.
+struct Chars<'a> {
+    chars: str::Chars<'a>,
+    read: usize,
+    line_start: usize,
+}
+
+impl<'a> Chars<'a> {
+    fn new(source: &'a str) -> Self {
+        Chars {
+            chars: source.chars(),
+            read: 0,
+            line_start: 0,
+        }
+    }
+
+    /// Column of the last character read.
+    fn column(&self) -> u32 {
+        (self.read - self.line_start) as u32
+    }
+
+    /// The next character, without reading it.
+    fn peek(&self) -> Option<char> {
+        self.chars.clone().next()
+    }
+
+    /// Marks the last character read as the end of a line.
+    fn end_line(&mut self) {
+        self.line_start = self.read;
+    }
+}
+
+impl Iterator for Chars<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c.is_some() {
+            self.read += 1;
+        }
+
+        c
+    }
+}
 
 pub fn tokenize(file_contents: String) -> (Vec<Token>, i32) {
     let mut status_code: i32 = 0;
     let mut tokens: Vec<Token> = vec![];
     let mut line_number = 1;
     let mut prev_lexeme = ' ';
-    let mut char_iter = file_contents.chars();
+    let mut char_iter = Chars::new(&file_contents);
     let mut c = char_iter.next();
 
     loop {
         match c {
             None => break,
-            Some('\t') | Some(' ') => c = char_iter.next(),
+            Some('\t') | Some(' ') => {
+                c = char_iter.next();
+                prev_lexeme = ' ';
+            }
             Some('\n') => {
+                char_iter.end_line();
                 c = char_iter.next();
                 line_number += 1;
                 prev_lexeme = ' ';
             }
             Some('"') => {
-                if let Ok(token) = get_string_literal(line_number, &mut char_iter) {
+                let column = char_iter.column();
+                if let Ok(mut token) = get_string_literal(line_number, column, &mut char_iter) {
                     // for string
+                    token.column = column;
                     tokens.push(token);
                 } else {
                     status_code = 65;
@@ -31,28 +87,31 @@ pub fn tokenize(file_contents: String) -> (Vec<Token>, i32) {
             Some(ch) => {
                 if ch.is_ascii_digit() {
                     // for numbers
-                    let (ch, token) = get_numeric_literal(ch, &mut char_iter, line_number);
+                    let column = char_iter.column();
+                    let (ch, mut token) = get_numeric_literal(ch, &mut char_iter, line_number);
+                    token.column = column;
                     tokens.push(token);
                     c = Some(ch);
-                    prev_lexeme = ch;
+                    prev_lexeme = ' ';
                     continue;
                 }
 
-                let token = Token::get_token(ch, prev_lexeme, line_number);
+                let mut token = Token::get_token(ch, prev_lexeme, line_number);
+                token.column = char_iter.column();
                 match token.token_type {
                     TokenType::INVALID => {
                         status_code = 65;
-                        eprintln!(
-                            "[line {line_number}] Error: Unexpected character: {}",
-                            token.lexeme
-                        );
+                        let message = format!("Unexpected character: {}", token.lexeme);
+                        let short = format!("[line {}] Error: {}", line_number, message);
+                        report(&Diagnostic::new(short, &message, Label::new(&token, "")));
                         prev_lexeme = ' ';
                         c = char_iter.next();
                         continue;
                     }
                     TokenType::IDENTIFIER => {
-                        let (ch, identifier_token) =
+                        let (ch, mut identifier_token) =
                             get_identifier(ch, &mut char_iter, line_number);
+                        identifier_token.column = token.column;
                         tokens.push(identifier_token);
                         prev_lexeme = ' ';
                         c = Some(ch);
@@ -77,9 +136,11 @@ pub fn tokenize(file_contents: String) -> (Vec<Token>, i32) {
                     }
                     _ => {
                         match token.lexeme.as_str() {
-                            "==" | "!=" | ">=" | "<=" => {
-                                // for comparisons
+                            "==" | "!=" | ">=" | "<=" | "+=" | "-=" | "*=" | "/=" | "++" | "--"
+                            | "**" | "+%" | "-%" | "*%" | "<<" | ">>" | "=>" => {
+                                // for two character operators
                                 tokens.pop();
+                                token.column -= 1;
                                 prev_lexeme = ' ';
                             }
                             _ => {
@@ -105,7 +166,7 @@ pub fn tokenize(file_contents: String) -> (Vec<Token>, i32) {
     (tokens, status_code)
 }
 
-fn get_string_literal<I>(line_number: u32, char_iter: &mut I) -> Result<Token, ()>
+fn get_string_literal<I>(line_number: u32, column: u32, char_iter: &mut I) -> Result<Token, ()>
 where
     I: Iterator<Item = char>,
 {
@@ -114,7 +175,14 @@ where
     loop {
         match c {
             None => {
-                eprintln!("[line {}] Error: Unterminated string.", line_number);
+                let short = format!("[line {}] Error: Unterminated string.", line_number);
+                let start = Label {
+                    line: line_number,
+                    column,
+                    len: 1,
+                    message: String::from("string starts here"),
+                };
+                report(&Diagnostic::new(short, "Unterminated string.", start));
                 return Err(());
             }
             Some('"') => break,
@@ -134,10 +202,11 @@ where
     ));
 }
 
-fn get_numeric_literal<I>(first_digit: char, char_iter: &mut I, line_number: u32) -> (char, Token)
-where
-    I: Iterator<Item = char>,
-{
+fn get_numeric_literal(
+    first_digit: char,
+    char_iter: &mut Chars,
+    line_number: u32,
+) -> (char, Token) {
     let mut c = char_iter.next();
     let mut numeric_val = String::from(first_digit);
     let mut has_decimal_point = false;
@@ -153,6 +222,17 @@ where
                 numeric_val = format!("{}.", numeric_val);
                 has_decimal_point = true;
             }
+            Some('n') if !has_decimal_point && !continues_identifier(char_iter, line_number) => {
+                // `123n` is a big integer, but `123name` stays a number and an identifier
+                let digits = BigInt::parse(&numeric_val).unwrap().to_string();
+                let token = Token::new(
+                    TokenType::BIGINT,
+                    format!("{}n", numeric_val),
+                    digits,
+                    line_number,
+                );
+                return (char_iter.next().unwrap_or(' '), token);
+            }
             Some(val) => {
                 if !val.is_ascii_digit() {
                     ch = val;
@@ -179,6 +259,12 @@ where
     )
 }
 
+fn continues_identifier(char_iter: &Chars, line_number: u32) -> bool {
+    char_iter.peek().is_some_and(|next| {
+        Token::get_token(next, ' ', line_number).token_type == TokenType::IDENTIFIER
+    })
+}
+
 fn get_identifier<I>(first_char: char, char_iter: &mut I, line_number: u32) -> (char, Token)
 where
     I: Iterator<Item = char>,
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use crate::tokenize;
-    use codecrafters_interpreter::{Token, TokenType};
+    use crate::tokenizer::{tokenize, tokenize_lossless, TriviaKind};
+    use crate::{Token, TokenType};
 
     fn destructure(token: Token) -> (TokenType, String, String) {
         let Token {
@@ -9,6 +9,7 @@ mod tests {
             lexeme,
             literal,
             line_num: _,
+            ..
         } = token;
 
         (token_type, lexeme, literal)
@@ -100,4 +101,199 @@ mod tests {
             (TokenType::EOF, String::from(""), String::from("null"))
         );
     }
+
+    #[test]
+    fn big_integer_literals_take_an_n_suffix() {
+        let (res, status_code) = tokenize("0012n 5name 1.5n".to_string());
+        assert_eq!(status_code, 0);
+        assert_eq!(
+            destructure(res[0].clone()),
+            (TokenType::BIGINT, String::from("0012n"), String::from("12"))
+        );
+        assert_eq!(res[1].token_type, TokenType::NUMBER);
+        assert_eq!(res[2].lexeme, "name");
+        assert_eq!(res[3].token_type, TokenType::NUMBER);
+        assert_eq!(res[4].lexeme, "n");
+    }
+
+    #[test]
+    fn map_punctuation_tokenization() {
+        let (res, _) = tokenize("m[\"a\"]: {}".to_string());
+        let types: Vec<TokenType> = res.into_iter().map(|token| token.token_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::IDENTIFIER,
+                TokenType::LEFT_BRACKET,
+                TokenType::STRING,
+                TokenType::RIGHT_BRACKET,
+                TokenType::COLON,
+                TokenType::LEFT_BRACE,
+                TokenType::RIGHT_BRACE,
+                TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn ternary_punctuation_tokenization() {
+        let (res, _) = tokenize("a ? b : c".to_string());
+        let types: Vec<TokenType> = res.into_iter().map(|token| token.token_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::IDENTIFIER,
+                TokenType::QUESTION,
+                TokenType::IDENTIFIER,
+                TokenType::COLON,
+                TokenType::IDENTIFIER,
+                TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn tokens_record_their_column() {
+        let (res, _) = tokenize("var a = 12;\n  if (a >= \"x\") {}".to_string());
+        let positions: Vec<(String, u32, u32)> = res
+            .iter()
+            .map(|token| (token.lexeme.clone(), token.line_num, token.column))
+            .collect();
+        let expected = [
+            ("var", 1, 1),
+            ("a", 1, 5),
+            ("=", 1, 7),
+            ("12", 1, 9),
+            (";", 1, 11),
+            ("if", 2, 3),
+            ("(", 2, 6),
+            ("a", 2, 7),
+            (">=", 2, 9),
+            ("\"x\"", 2, 12),
+            (")", 2, 15),
+            ("{", 2, 17),
+            ("}", 2, 18),
+        ];
+        for (position, (lexeme, line, column)) in positions.iter().zip(expected) {
+            assert_eq!(*position, (lexeme.to_string(), line, column));
+        }
+    }
+
+    #[test]
+    fn operators_next_to_numbers_and_spaces_stay_separate() {
+        let (res, status_code) = tokenize("10/2 = = 1".to_string());
+        let lexemes: Vec<&str> = res.iter().map(|token| token.lexeme.as_str()).collect();
+        assert_eq!(status_code, 0);
+        assert_eq!(lexemes, ["10", "/", "2", "=", "=", "1", ""]);
+    }
+
+    #[test]
+    fn compound_operators_are_single_tokens() {
+        let (res, _) = tokenize("a+=1; b-=c*=d/=2; i++ + --j; k+++l".to_string());
+        let lexemes: Vec<&str> = res.iter().map(|token| token.lexeme.as_str()).collect();
+        assert_eq!(
+            lexemes,
+            [
+                "a", "+=", "1", ";", "b", "-=", "c", "*=", "d", "/=", "2", ";", "i", "++", "+",
+                "--", "j", ";", "k", "++", "+", "l", ""
+            ]
+        );
+        assert_eq!(res[1].token_type, TokenType::PLUS_EQUAL);
+        assert_eq!(res[1].column, 2);
+        assert_eq!(res[15].token_type, TokenType::MINUS_MINUS);
+    }
+
+    #[test]
+    fn match_arrows_are_single_tokens() {
+        let (res, _) = tokenize("match (x) { a=>1, b == >c }".to_string());
+        let types: Vec<TokenType> = res.iter().map(|token| token.token_type).collect();
+        assert_eq!(
+            types,
+            [
+                TokenType::MATCH,
+                TokenType::LEFT_PAREN,
+                TokenType::IDENTIFIER,
+                TokenType::RIGHT_PAREN,
+                TokenType::LEFT_BRACE,
+                TokenType::IDENTIFIER,
+                TokenType::FAT_ARROW,
+                TokenType::NUMBER,
+                TokenType::COMMA,
+                TokenType::IDENTIFIER,
+                TokenType::EQUAL_EQUAL,
+                TokenType::GREATER,
+                TokenType::IDENTIFIER,
+                TokenType::RIGHT_BRACE,
+                TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn bitwise_and_wrapping_operators_are_single_tokens() {
+        let (res, status_code) = tokenize("a<<2>>b & ~c|d^e +% f-%g*%h <= i".to_string());
+        let types: Vec<TokenType> = res.iter().map(|token| token.token_type).collect();
+        assert_eq!(status_code, 0);
+        assert_eq!(
+            types,
+            [
+                TokenType::IDENTIFIER,
+                TokenType::LESS_LESS,
+                TokenType::NUMBER,
+                TokenType::GREATER_GREATER,
+                TokenType::IDENTIFIER,
+                TokenType::AMPERSAND,
+                TokenType::TILDE,
+                TokenType::IDENTIFIER,
+                TokenType::PIPE,
+                TokenType::IDENTIFIER,
+                TokenType::CARET,
+                TokenType::IDENTIFIER,
+                TokenType::PLUS_PERCENT,
+                TokenType::IDENTIFIER,
+                TokenType::MINUS_PERCENT,
+                TokenType::IDENTIFIER,
+                TokenType::STAR_PERCENT,
+                TokenType::IDENTIFIER,
+                TokenType::LESS_EQUAL,
+                TokenType::IDENTIFIER,
+                TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn lossless_tokens_reproduce_the_source() {
+        for source in [
+            "",
+            "// only a comment",
+            "var a = 1; // one\r\n\n\t{ print a / 2 ; }\n",
+            "print \"multi\nline\" # @;\n// trailing",
+            "print \"unterminated",
+        ] {
+            let (tokens, _) = tokenize_lossless(source);
+            let text: String = tokens.iter().map(|token| token.to_string()).collect();
+            assert_eq!(text, source);
+        }
+    }
+
+    #[test]
+    fn trivia_attaches_to_the_nearest_token() {
+        let (tokens, _) = tokenize_lossless("  var a; // note\n\nprint a;\n");
+        let kinds = |trivia: &[crate::tokenizer::Trivia]| -> Vec<TriviaKind> {
+            trivia.iter().map(|trivia| trivia.kind).collect()
+        };
+        assert_eq!(kinds(&tokens[0].leading), [TriviaKind::Whitespace]);
+        assert_eq!(
+            kinds(&tokens[2].trailing),
+            [TriviaKind::Whitespace, TriviaKind::Comment]
+        );
+        assert_eq!(
+            kinds(&tokens[3].leading),
+            [TriviaKind::Newline, TriviaKind::Newline]
+        );
+        assert_eq!(tokens[3].token.lexeme, "print");
+        assert_eq!(kinds(&tokens[6].leading), [TriviaKind::Newline]);
+        assert_eq!(tokens[6].token.lexeme, "");
+    }
 }
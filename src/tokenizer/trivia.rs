@@ -0,0 +1,197 @@
+use super::tokenize;
+use crate::json::Json;
+use crate::{Token, TokenType};
+use std::fmt;
+
+/// Version of the `tokenize --format json` output.
+pub const TOKEN_STREAM_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriviaKind {
+    /// Spaces and tabs.
+    Whitespace,
+    /// A single line break, `\n` or `\r\n`.
+    Newline,
+    /// A `//` comment, without the line break that ends it.
+    Comment,
+    /// Characters the tokenizer rejected, or an unterminated string.
+    Skipped,
+}
+
+impl TriviaKind {
+    fn name(&self) -> &'static str {
+        match self {
+            TriviaKind::Whitespace => "whitespace",
+            TriviaKind::Newline => "newline",
+            TriviaKind::Comment => "comment",
+            TriviaKind::Skipped => "skipped",
+        }
+    }
+}
+
+/// Source text between tokens that the parser doesn't care about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+}
+
+/// A token with the trivia around it. Trailing trivia runs up to the end of the token's line;
+/// everything else before a token is its leading trivia, and whatever follows the last token
+/// belongs to the EOF token.
+#[derive(Debug, Clone)]
+pub struct LosslessToken {
+    pub leading: Vec<Trivia>,
+    pub token: Token,
+    pub trailing: Vec<Trivia>,
+}
+
+impl fmt::Display for LosslessToken {
+    /// The exact source text the token came from, trivia included.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for trivia in self.leading.iter() {
+            write!(f, "{}", trivia.text)?;
+        }
+        write!(f, "{}", self.token.lexeme)?;
+        for trivia in self.trailing.iter() {
+            write!(f, "{}", trivia.text)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl LosslessToken {
+    pub fn to_json(&self) -> Json {
+        let trivia = |list: &[Trivia]| {
+            Json::Array(
+                list.iter()
+                    .map(|trivia| {
+                        Json::object(vec![
+                            ("kind", Json::from(trivia.kind.name())),
+                            ("text", Json::from(trivia.text.as_str())),
+                        ])
+                    })
+                    .collect(),
+            )
+        };
+        let literal = match self.token.token_type {
+            TokenType::STRING | TokenType::BIGINT => Json::from(self.token.literal.as_str()),
+            TokenType::NUMBER => Json::from(self.token.literal.parse::<f64>().ok()),
+            _ => Json::Null,
+        };
+
+        Json::object(vec![
+            ("type", Json::from(format!("{:?}", self.token.token_type))),
+            ("lexeme", Json::from(self.token.lexeme.as_str())),
+            ("literal", literal),
+            ("line", Json::from(self.token.line_num)),
+            ("column", Json::from(self.token.column)),
+            ("leading", trivia(&self.leading)),
+            ("trailing", trivia(&self.trailing)),
+        ])
+    }
+}
+
+/// Serializes a lossless token stream as `{"version", "tokens"}`.
+pub fn tokens_json(tokens: &[LosslessToken]) -> Json {
+    Json::object(vec![
+        ("version", Json::from(TOKEN_STREAM_VERSION)),
+        (
+            "tokens",
+            Json::Array(tokens.iter().map(LosslessToken::to_json).collect()),
+        ),
+    ])
+}
+
+/// Tokenizes `source` keeping whitespace and comments, so that concatenating the tokens gives
+/// back the source byte for byte. Errors are reported the same way `tokenize` reports them.
+pub fn tokenize_lossless(source: &str) -> (Vec<LosslessToken>, i32) {
+    let (tokens, status_code) = tokenize(source.to_string());
+    let mut stream = vec![];
+    let mut pos = 0;
+    let mut leading = vec![];
+    for token in tokens {
+        if token.token_type == TokenType::EOF {
+            while pos < source.len() {
+                pos += push_trivia(&mut leading, &source[pos..], true);
+            }
+            stream.push(LosslessToken {
+                leading,
+                token,
+                trailing: vec![],
+            });
+            break;
+        }
+
+        // tokens are slices of the source, so only trivia can come before the next one
+        while pos < source.len() {
+            let rest = &source[pos..];
+            if trivia_at(rest).0 != TriviaKind::Skipped || !rest.starts_with(&token.lexeme) {
+                pos += push_trivia(&mut leading, rest, true);
+            } else {
+                break;
+            }
+        }
+        pos += token.lexeme.len();
+
+        let mut trailing = vec![];
+        while pos < source.len() {
+            match push_trivia(&mut trailing, &source[pos..], false) {
+                0 => break,
+                len => pos += len,
+            }
+        }
+
+        stream.push(LosslessToken {
+            leading: std::mem::take(&mut leading),
+            token,
+            trailing,
+        });
+    }
+
+    (stream, status_code)
+}
+
+/// Moves the trivia at the start of `rest` onto `list`, returning its length in bytes. Line
+/// breaks and rejected characters are only taken for leading trivia.
+fn push_trivia(list: &mut Vec<Trivia>, rest: &str, leading: bool) -> usize {
+    let (kind, len) = trivia_at(rest);
+    if !leading && matches!(kind, TriviaKind::Newline | TriviaKind::Skipped) {
+        return 0;
+    }
+
+    let text = &rest[..len];
+    match list.last_mut() {
+        // runs of rejected characters read better as one piece
+        Some(last) if kind == TriviaKind::Skipped && last.kind == TriviaKind::Skipped => {
+            last.text.push_str(text)
+        }
+        _ => list.push(Trivia {
+            kind,
+            text: text.to_string(),
+        }),
+    }
+
+    len
+}
+
+/// The kind and length of the trivia `rest` starts with. Anything that isn't whitespace or a
+/// comment counts as skipped, so callers check for a token first.
+fn trivia_at(rest: &str) -> (TriviaKind, usize) {
+    if rest.starts_with("//") {
+        (TriviaKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+    } else if rest.starts_with('\n') || rest.starts_with("\r\n") {
+        (TriviaKind::Newline, rest.find('\n').unwrap() + 1)
+    } else if rest.starts_with([' ', '\t']) {
+        (
+            TriviaKind::Whitespace,
+            rest.find(|c| c != ' ' && c != '\t').unwrap_or(rest.len()),
+        )
+    } else {
+        (
+            TriviaKind::Skipped,
+            rest.chars().next().map_or(0, char::len_utf8),
+        )
+    }
+}
@@ -0,0 +1,395 @@
+use crate::builtins;
+use crate::{Expr, Pattern, Statement, Token, TokenType};
+use std::collections::HashMap;
+use std::fmt;
+mod tests;
+
+/// A static type. Unannotated variables and anything the checker can't work out are `Any`,
+/// which is compatible with every type, so unannotated code never produces errors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Type {
+    Number,
+    String,
+    Bool,
+    Nil,
+    Map,
+    Any,
+}
+
+impl Type {
+    /// The type named in an annotation such as `var x: number`.
+    pub fn from_name(name: &str) -> Option<Type> {
+        match name {
+            "number" => Some(Type::Number),
+            "string" => Some(Type::String),
+            "bool" => Some(Type::Bool),
+            "nil" => Some(Type::Nil),
+            "map" => Some(Type::Map),
+            "any" => Some(Type::Any),
+            _ => None,
+        }
+    }
+
+    fn accepts(self, other: Type) -> bool {
+        self == Type::Any || other == Type::Any || self == other
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Type::Number => "number",
+            Type::String => "string",
+            Type::Bool => "bool",
+            Type::Nil => "nil",
+            Type::Map => "map",
+            Type::Any => "any",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TypeError {
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[line {}, column {}] Type error: {}.",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+/// Checks a parsed program against its annotations, returning every mismatch found.
+pub fn check(program: &[Expr]) -> Vec<TypeError> {
+    let mut checker = Checker {
+        scopes: vec![HashMap::new()],
+        errors: vec![],
+    };
+    checker.check_all(program);
+
+    checker.errors
+}
+
+/// The type of a single expression, with every variable it mentions treated as `Any`.
+pub fn infer(expr: &Expr) -> Type {
+    let mut checker = Checker {
+        scopes: vec![HashMap::new()],
+        errors: vec![],
+    };
+
+    checker.infer(expr)
+}
+
+struct Checker {
+    scopes: Vec<HashMap<String, Type>>,
+    errors: Vec<TypeError>,
+}
+
+impl Checker {
+    fn check_all(&mut self, exprs: &[Expr]) {
+        for expr in exprs {
+            self.check_statement(expr);
+        }
+    }
+
+    fn check_statement(&mut self, expr: &Expr) {
+        let statement = match expr {
+            Expr::Scope(_, exprs) => {
+                self.scopes.push(HashMap::new());
+                self.check_all(exprs);
+                self.scopes.pop();
+                return;
+            }
+            Expr::Stmt(statement) => statement,
+            _ => {
+                self.infer(expr);
+                return;
+            }
+        };
+
+        match statement {
+            Statement::PrintStmt(expr) => {
+                self.infer(expr);
+            }
+            Statement::DeclarationStmt(variable, annotation, value) => {
+                let value_type = value.as_ref().map(|value| self.infer(value));
+                let declared = annotation
+                    .as_ref()
+                    .and_then(|name| Type::from_name(&name.lexeme))
+                    .unwrap_or(Type::Any);
+                if let Some(value_type) = value_type {
+                    if !declared.accepts(value_type) {
+                        self.error(
+                            variable,
+                            format!(
+                                "Cannot initialize '{}' of type {} with {}",
+                                variable.lexeme, declared, value_type
+                            ),
+                        );
+                    }
+                }
+
+                if let Some(scope) = self.scopes.last_mut() {
+                    scope.insert(variable.lexeme.clone(), declared);
+                }
+            }
+            Statement::IfStmt(conditionals) => {
+                for conditional in conditionals {
+                    self.infer(&conditional.0);
+                    self.check_statement(&conditional.1);
+                }
+            }
+            Statement::WhileStmt(conditional) => {
+                self.infer(&conditional.0);
+                self.check_statement(&conditional.1);
+            }
+            Statement::MatchStmt(_, value, arms) => {
+                let value = self.infer(value);
+                for arm in arms {
+                    let mut scope = HashMap::new();
+                    for pattern in arm.patterns.iter() {
+                        if let Pattern::Binding(name) = pattern {
+                            scope.insert(name.lexeme.clone(), value);
+                        }
+                    }
+                    self.scopes.push(scope);
+                    if let Some(guard) = &arm.guard {
+                        self.infer(guard);
+                    }
+                    self.check_statement(&arm.body);
+                    self.scopes.pop();
+                }
+            }
+            Statement::ForStmt(init, condition, update, body) => {
+                self.scopes.push(HashMap::new());
+                if let Some(init) = init {
+                    self.check_statement(init);
+                }
+                self.infer(condition);
+                if let Some(update) = update {
+                    self.check_statement(update);
+                }
+                self.check_statement(body);
+                self.scopes.pop();
+            }
+            Statement::ImportStmt(_, names) => {
+                for name in names.iter().flatten() {
+                    if let Some(scope) = self.scopes.last_mut() {
+                        scope.insert(name.lexeme.clone(), Type::Any);
+                    }
+                }
+            }
+            Statement::ExportStmt(declaration) => self.check_statement(declaration),
+            Statement::AssignmentStmt(..)
+            | Statement::IndexAssignmentStmt(..)
+            | Statement::CompoundAssignmentStmt(..)
+            | Statement::IncrementStmt(..) => {
+                self.infer(expr);
+            }
+        }
+    }
+
+    /// Works out the type of an expression, reporting mismatches inside it. After an error the
+    /// expression is `Any`, so one mistake isn't reported again by every enclosing expression.
+    fn infer(&mut self, expr: &Expr) -> Type {
+        match expr {
+            Expr::Literal(token) => match token.token_type {
+                TokenType::NUMBER | TokenType::BIGINT => Type::Number,
+                TokenType::STRING => Type::String,
+                TokenType::TRUE | TokenType::FALSE => Type::Bool,
+                TokenType::NIL => Type::Nil,
+                TokenType::IDENTIFIER => match self.lookup(&token.lexeme) {
+                    Some(var_type) => var_type,
+                    None if builtins::constant(&token.lexeme, 0).is_some() => Type::Number,
+                    None => Type::Any,
+                },
+                _ => Type::Any,
+            },
+            Expr::Grouping(expr) => self.infer(expr),
+            Expr::Unary(operator, right) => {
+                let right = self.infer(right);
+                match operator.token_type {
+                    TokenType::MINUS | TokenType::TILDE => {
+                        self.expect_numbers(operator, &[right], "Operand must be a number")
+                    }
+                    _ => Type::Bool,
+                }
+            }
+            Expr::Binary(left, operator, right) => {
+                let left = self.infer(left);
+                let right = self.infer(right);
+                self.infer_binary(operator, left, right)
+            }
+            Expr::Logical(left, _, right) => {
+                let left = self.infer(left);
+                let right = self.infer(right);
+                match left == right {
+                    true => left,
+                    false => Type::Any,
+                }
+            }
+            Expr::Ternary(condition, _, then, otherwise) => {
+                self.infer(condition);
+                let then = self.infer(then);
+                let otherwise = self.infer(otherwise);
+                match then == otherwise {
+                    true => then,
+                    false => Type::Any,
+                }
+            }
+            Expr::Stmt(Statement::AssignmentStmt(variable, value)) => {
+                let value_type = self.infer(value);
+                match self.lookup(&variable.lexeme) {
+                    Some(declared) if !declared.accepts(value_type) => {
+                        self.error(
+                            variable,
+                            format!(
+                                "Cannot assign {} to '{}' of type {}",
+                                value_type, variable.lexeme, declared
+                            ),
+                        );
+                        Type::Any
+                    }
+                    _ => value_type,
+                }
+            }
+            Expr::Stmt(Statement::IndexAssignmentStmt(target, bracket, key, value)) => {
+                let target = self.infer(target);
+                self.infer(key);
+                let value_type = self.infer(value);
+                match target {
+                    Type::String => self.error(bracket, String::from("Strings are immutable")),
+                    Type::Map | Type::Any => {}
+                    _ => self.error(
+                        bracket,
+                        String::from("Only maps and strings can be indexed"),
+                    ),
+                }
+
+                value_type
+            }
+            Expr::Stmt(Statement::CompoundAssignmentStmt(target, operator, value)) => {
+                let target = self.infer(target);
+                let value = self.infer(value);
+                let mut arithmetic = operator.clone();
+                if let Some(token_type) = operator.token_type.arithmetic_operator() {
+                    arithmetic.token_type = token_type;
+                }
+                self.infer_binary(&arithmetic, target, value)
+            }
+            Expr::Stmt(Statement::IncrementStmt(target, operator, _)) => {
+                let target = self.infer(target);
+                self.expect_numbers(operator, &[target], "Operand must be a number")
+            }
+            Expr::Stmt(_) | Expr::Scope(..) => Type::Any,
+            Expr::Map(_, entries) => {
+                for (key, value) in entries {
+                    self.infer(key);
+                    self.infer(value);
+                }
+
+                Type::Map
+            }
+            Expr::Index(target, bracket, key) => {
+                let target = self.infer(target);
+                self.infer(key);
+                match target {
+                    Type::String => Type::String,
+                    Type::Map | Type::Any => Type::Any,
+                    _ => {
+                        self.error(
+                            bracket,
+                            String::from("Only maps and strings can be indexed"),
+                        );
+                        Type::Any
+                    }
+                }
+            }
+            Expr::Call(_, args) => {
+                for arg in args {
+                    self.infer(arg);
+                }
+
+                Type::Any
+            }
+        }
+    }
+
+    fn infer_binary(&mut self, operator: &Token, left: Type, right: Type) -> Type {
+        match operator.token_type {
+            TokenType::PLUS => match (left, right) {
+                (Type::Number, Type::Number) => Type::Number,
+                (Type::String, Type::String) => Type::String,
+                (Type::Any, _) | (_, Type::Any) => Type::Any,
+                _ => {
+                    self.error(
+                        operator,
+                        String::from("Operands must be two numbers or two strings"),
+                    );
+                    Type::Any
+                }
+            },
+            TokenType::MINUS
+            | TokenType::STAR
+            | TokenType::SLASH
+            | TokenType::PERCENT
+            | TokenType::DIV
+            | TokenType::STAR_STAR
+            | TokenType::PLUS_PERCENT
+            | TokenType::MINUS_PERCENT
+            | TokenType::STAR_PERCENT
+            | TokenType::AMPERSAND
+            | TokenType::PIPE
+            | TokenType::CARET
+            | TokenType::LESS_LESS
+            | TokenType::GREATER_GREATER => {
+                self.expect_numbers(operator, &[left, right], "Operands must be numbers")
+            }
+            TokenType::GREATER
+            | TokenType::GREATER_EQUAL
+            | TokenType::LESS
+            | TokenType::LESS_EQUAL => {
+                match self.expect_numbers(operator, &[left, right], "Operands must be numbers") {
+                    Type::Number => Type::Bool,
+                    _ => Type::Any,
+                }
+            }
+            _ => Type::Bool,
+        }
+    }
+
+    fn expect_numbers(&mut self, operator: &Token, operands: &[Type], message: &str) -> Type {
+        if operands
+            .iter()
+            .all(|operand| Type::Number.accepts(*operand))
+        {
+            return Type::Number;
+        }
+
+        self.error(operator, String::from(message));
+        Type::Any
+    }
+
+    fn lookup(&self, name: &str) -> Option<Type> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+    }
+
+    fn error(&mut self, token: &Token, message: String) {
+        self.errors.push(TypeError {
+            line: token.line_num,
+            column: token.column,
+            message,
+        });
+    }
+}
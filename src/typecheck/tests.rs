@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+    use crate::typecheck::{check, TypeError};
+    use crate::{parse, Interpreter, SharedBuffer};
+
+    fn errors(source: &str) -> Vec<String> {
+        let program = parse(source).unwrap();
+        check(&program).iter().map(TypeError::to_string).collect()
+    }
+
+    #[test]
+    fn reports_mismatches_with_position() {
+        let source = "var x: number = \"one\";
+var y: string = \"a\";
+y = 2;
+print -y;
+{
+  var z: bool = 1 < 2;
+  print z + 1;
+}";
+        assert_eq!(
+            errors(source),
+            [
+                "[line 1, column 5] Type error: Cannot initialize 'x' of type number with string.",
+                "[line 3, column 1] Type error: Cannot assign number to 'y' of type string.",
+                "[line 4, column 7] Type error: Operand must be a number.",
+                "[line 7, column 11] Type error: Operands must be two numbers or two strings.",
+            ]
+        );
+    }
+
+    #[test]
+    fn infers_through_expressions() {
+        assert_eq!(
+            errors("var s: string = \"a\" + \"b\";\nvar n: number = (1 + 2) * -3;"),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            errors("var b: bool = \"a\" + \"b\";"),
+            ["[line 1, column 5] Type error: Cannot initialize 'b' of type bool with string."]
+        );
+    }
+
+    #[test]
+    fn checks_compound_assignments() {
+        let source = "var n: number = 1;\nn += 2;\nn++;\nvar s: string = \"a\";\ns -= \"b\";\ns++;";
+        assert_eq!(
+            errors(source),
+            [
+                "[line 5, column 3] Type error: Operands must be numbers.",
+                "[line 6, column 2] Type error: Operand must be a number.",
+            ]
+        );
+    }
+
+    #[test]
+    fn unannotated_code_is_unchanged() {
+        let source = "var a = 1;\na = \"now a string\";\nvar m = {\"k\": 1};\nprint a + m[\"k\"];";
+        assert!(errors(source).is_empty());
+
+        let out = SharedBuffer::new();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(out.clone());
+        interpreter
+            .run_source("var x: number = 1;\nx = x + 1;\nprint x;")
+            .unwrap();
+        assert_eq!(out.contents(), "2\n");
+    }
+
+    #[test]
+    fn unknown_type_names_are_parse_errors() {
+        assert!(parse("var x: integer = 1;").is_err());
+        assert!(parse("var x: nil;").is_ok());
+    }
+}
@@ -0,0 +1,17 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Runs the annotated scripts in `tests/lox` through the `test` subcommand.
+#[test]
+fn lox_scripts_match_their_annotations() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/lox");
+    let output = Command::new(env!("CARGO_BIN_EXE_codecrafters-interpreter"))
+        .arg("test")
+        .arg(&dir)
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.starts_with("All "), "{}", stdout);
+}
@@ -0,0 +1,84 @@
+use std::env;
+use std::fs;
+use std::process::{Command, Output};
+
+/// Runs the real binary, so the limits are checked against the stack it actually has rather
+/// than a test thread's.
+fn interpreter(command: &str, name: &str, source: &str, flags: &[&str]) -> Output {
+    let path = env::temp_dir().join(format!("lox-limits-{}-{}.lox", std::process::id(), name));
+    fs::write(&path, source).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_codecrafters-interpreter"))
+        .arg(command)
+        .arg(&path)
+        .args(flags)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+
+    output
+}
+
+fn sum(terms: usize) -> String {
+    format!("print {};", vec!["1"; terms].join(" + "))
+}
+
+#[test]
+fn evaluation_depth_fits_the_stack() {
+    let output = interpreter("run", "sum", &sum(999), &[]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "999\n");
+
+    // a long flat expression isn't nesting a script author would notice
+    let output = interpreter("run", "long-sum", &sum(3500), &[]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "3500\n");
+
+    let output = interpreter("run", "deep-sum", &sum(4500), &[]);
+    assert_eq!(output.status.code(), Some(75));
+
+    let output = interpreter("run", "raised", &sum(4500), &["--max-depth", "5000"]);
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn variables_resolve_in_deeply_nested_blocks() {
+    let source = format!(
+        "var x = 1;{}var y = x; y++; x = y; print y;{}print x;",
+        "{".repeat(130),
+        "}".repeat(130)
+    );
+    let output = interpreter("run", "deep-blocks", &source, &[]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "2\n2\n");
+}
+
+#[test]
+fn excessive_nesting_is_a_parse_error() {
+    let nested = |depth: usize| format!("print {}1{};", "(".repeat(depth), ")".repeat(depth));
+    let status = |name: &str, source: &str| interpreter("run", name, source, &[]).status.code();
+    assert_eq!(status("parens", &nested(100)), Some(0));
+    assert_eq!(status("deep-parens", &nested(1000)), Some(65));
+
+    let blocks = format!("{}{}", "{".repeat(1000), "}".repeat(1000));
+    assert_eq!(status("blocks", &blocks), Some(65));
+}
+
+#[test]
+fn every_command_stops_at_the_nesting_limit() {
+    let nested = |depth: usize| format!("print {}1{};", "(".repeat(depth), ")".repeat(depth));
+    for (command, flags) in [
+        ("run", vec![]),
+        ("lint", vec![]),
+        ("typecheck", vec![]),
+        ("parse", vec!["--format", "sexpr"]),
+        ("parse", vec!["--format", "json"]),
+    ] {
+        let output = interpreter(command, "deepest", &nested(125), &flags);
+        assert_eq!(output.status.code(), Some(0), "{} {:?}", command, flags);
+
+        let output = interpreter(command, "too-deep", &nested(200), &flags);
+        assert_eq!(output.status.code(), Some(65), "{} {:?}", command, flags);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Too much nesting."), "{}", stderr);
+    }
+}